@@ -0,0 +1,243 @@
+//! dudect-style timing leakage tests for the constant-time operations of w25519
+//!
+//! This implements the methodology of "Dude, is my code constant time?" (Reparaz, Balasch
+//! and Verbauwhede, 2017): each operation is timed on inputs from two classes, a fixed input
+//! and random inputs, assigned in random order.  Welch's t-test is run on the raw timings and
+//! on timings cropped at several percentiles; a large |t| is evidence that the running time
+//! depends on the input.
+//!
+//! ```text
+//! cargo run --release -p ct-harness -- [add|mul|ct_eq|diffie_hellman|all] [samples]
+//! ```
+//!
+//! Run it in release mode on the target hardware, with as little other load as possible.
+//! As with dudect, the absence of detected leakage is evidence, not a proof.
+
+use std::env;
+use std::hint::black_box;
+use std::process;
+use std::time::Instant;
+
+use curve25519_dalek::constants::WEI25519_BASEPOINT;
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::weierstrass::WeierstrassPoint;
+use rand_core::{OsRng, RngCore};
+use subtle::ConstantTimeEq;
+use w25519::{PublicKey, StaticSecret};
+
+/// Number of cropping percentiles, as in dudect.
+const PERCENTILES: usize = 100;
+
+/// |t| above which leakage is reported as probable.
+const T_THRESHOLD_PROBABLE: f64 = 4.5;
+
+/// |t| above which leakage is reported as certain.
+const T_THRESHOLD_CERTAIN: f64 = 10.0;
+
+/// Number of random points to draw inputs from, since generating a point per sample would
+/// dominate the running time.
+const POOL_SIZE: usize = 64;
+
+/// Online Welch's t-test of two classes of measurements.
+#[derive(Clone, Default)]
+struct TTest {
+    n: [f64; 2],
+    mean: [f64; 2],
+    m2: [f64; 2],
+}
+
+impl TTest {
+    fn push(&mut self, class: usize, x: f64) {
+        self.n[class] += 1.0;
+        let delta = x - self.mean[class];
+        self.mean[class] += delta / self.n[class];
+        self.m2[class] += delta * (x - self.mean[class]);
+    }
+
+    fn samples(&self) -> f64 {
+        self.n[0].min(self.n[1])
+    }
+
+    fn t(&self) -> f64 {
+        let var0 = self.m2[0] / (self.n[0] - 1.0);
+        let var1 = self.m2[1] / (self.n[1] - 1.0);
+        let den = (var0 / self.n[0] + var1 / self.n[1]).sqrt();
+
+        if den > 0.0 {
+            (self.mean[0] - self.mean[1]) / den
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Time `op` on `samples` inputs, with class 0 getting `fixed` and class 1 fresh inputs from
+/// `random`.
+fn measure<I: Clone, F: FnMut(&I)>(
+    samples: usize,
+    fixed: I,
+    mut random: impl FnMut() -> I,
+    mut op: F,
+) -> Vec<(usize, f64)> {
+    // Prepare all inputs first so that generating them is not timed.
+    let inputs: Vec<(usize, I)> = (0..samples)
+        .map(|_| match OsRng.next_u32() & 1 {
+            0 => (0, fixed.clone()),
+            _ => (1, random()),
+        })
+        .collect();
+
+    inputs
+        .iter()
+        .map(|(class, input)| {
+            let start = Instant::now();
+            op(black_box(input));
+            (*class, start.elapsed().as_nanos() as f64)
+        })
+        .collect()
+}
+
+/// Run the t-tests on the measurements, returning the largest |t| and its sample count.
+fn analyze(measurements: &[(usize, f64)]) -> (f64, f64) {
+    if measurements.is_empty() {
+        return (0.0, 0.0);
+    }
+
+    let mut sorted: Vec<f64> = measurements.iter().map(|(_, t)| *t).collect();
+    sorted.sort_by(|a, b| a.partial_cmp(b).expect("timings are not NaN"));
+
+    // Cropping thresholds 1 - 0.5^(10 * (k + 1) / PERCENTILES), as in dudect
+    let thresholds: Vec<f64> = (0..PERCENTILES)
+        .map(|k| {
+            let p = 1.0 - 0.5f64.powf(10.0 * (k + 1) as f64 / PERCENTILES as f64);
+            sorted[((sorted.len() - 1) as f64 * p) as usize]
+        })
+        .collect();
+
+    let mut tests = vec![TTest::default(); PERCENTILES + 1];
+    for (class, x) in measurements {
+        tests[0].push(*class, *x);
+        for (test, threshold) in tests[1..].iter_mut().zip(thresholds.iter()) {
+            if x < threshold {
+                test.push(*class, *x);
+            }
+        }
+    }
+
+    tests
+        .iter()
+        .filter(|test| test.samples() > 10.0)
+        .map(|test| (test.t().abs(), test.samples()))
+        .fold((0.0, 0.0), |max, t| if t.0 > max.0 { t } else { max })
+}
+
+fn report(name: &str, measurements: &[(usize, f64)]) -> bool {
+    let (t, n) = analyze(measurements);
+    let verdict = if t > T_THRESHOLD_CERTAIN {
+        "definitely not constant time"
+    } else if t > T_THRESHOLD_PROBABLE {
+        "probably not constant time"
+    } else {
+        "no leakage detected"
+    };
+
+    println!("{:<16} max |t| = {:>8.3} ({:.0} samples per class): {}", name, t, n, verdict);
+    t <= T_THRESHOLD_PROBABLE
+}
+
+fn random_point() -> WeierstrassPoint {
+    Scalar::random(&mut OsRng) * WEI25519_BASEPOINT
+}
+
+fn pick(pool: &[WeierstrassPoint]) -> WeierstrassPoint {
+    pool[OsRng.next_u32() as usize % pool.len()]
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let op = args.get(1).map(String::as_str).unwrap_or("all");
+    let samples: Option<usize> = args.get(2).map(|s| {
+        s.parse().ok().filter(|&n| n > 0).unwrap_or_else(|| {
+            eprintln!("invalid sample count: {}", s);
+            process::exit(2);
+        })
+    });
+
+    let pool: Vec<WeierstrassPoint> = (0..POOL_SIZE).map(|_| random_point()).collect();
+    let mut passed = true;
+    let mut ran = false;
+
+    if op == "add" || op == "all" {
+        // Doubling the base point against adding distinct points
+        let g = WEI25519_BASEPOINT;
+        let m = measure(samples.unwrap_or(100_000), (g, g), || (g, pick(&pool)), |(p, q)| {
+            black_box(p + q);
+        });
+        passed &= report("add", &m);
+        ran = true;
+    }
+
+    if op == "mul" || op == "all" {
+        // Scalar one against random scalars
+        let m = measure(samples.unwrap_or(1_000), Scalar::one(), || Scalar::random(&mut OsRng), |s| {
+            black_box(s * WEI25519_BASEPOINT);
+        });
+        passed &= report("mul", &m);
+        ran = true;
+    }
+
+    if op == "ct_eq" || op == "all" {
+        // Equal points against distinct points
+        let p = pool[0];
+        let m = measure(samples.unwrap_or(100_000), (p, p), || (p, pick(&pool[1..])), |(p, q)| {
+            black_box(p.ct_eq(q));
+        });
+        passed &= report("ct_eq", &m);
+        ran = true;
+    }
+
+    if op == "diffie_hellman" || op == "all" {
+        // The smallest clamped secret key against random secret keys
+        let their_public = PublicKey::from(&StaticSecret::new(OsRng));
+        let m = measure(
+            samples.unwrap_or(1_000),
+            [0u8; 32],
+            || {
+                let mut bytes = [0u8; 32];
+                OsRng.fill_bytes(&mut bytes);
+                bytes
+            },
+            |bytes| {
+                black_box(StaticSecret::from(*bytes).diffie_hellman(&their_public));
+            },
+        );
+        passed &= report("diffie_hellman", &m);
+        ran = true;
+    }
+
+    if !ran {
+        eprintln!("usage: ct-harness [add|mul|ct_eq|diffie_hellman|all] [samples]");
+        process::exit(2);
+    }
+    if !passed {
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn welch_t_test() {
+        let same: Vec<(usize, f64)> = (0..1000).map(|i| (i % 2, (i % 7) as f64)).collect();
+        assert!(analyze(&same).0 < T_THRESHOLD_PROBABLE);
+
+        let shifted: Vec<(usize, f64)> = (0..1000)
+            .map(|i| (i % 2, (i % 7) as f64 + (i % 2) as f64 * 10.0))
+            .collect();
+        assert!(analyze(&shifted).0 > T_THRESHOLD_CERTAIN);
+
+        assert_eq!(analyze(&[]), (0.0, 0.0));
+    }
+}
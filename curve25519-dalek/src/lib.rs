@@ -276,6 +276,8 @@ extern crate subtle;
 extern crate bincode;
 #[cfg(feature = "serde")]
 extern crate serde;
+#[cfg(feature = "sec1")]
+pub extern crate sec1;
 
 // Internal macros. Must come first!
 #[macro_use]
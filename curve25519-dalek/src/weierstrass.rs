@@ -1,6 +1,6 @@
 //! Scalar multiplication on Wei25519, the Weierstrass form representation of Curve25519
 
-use core::ops::{Add, AddAssign, BitAndAssign, Mul, MulAssign};
+use core::ops::{Add, AddAssign, BitAndAssign, Mul, MulAssign, Neg, Sub};
 
 use field::FieldElement;
 use scalar::Scalar;
@@ -8,24 +8,46 @@ use scalar::Scalar;
 use traits::Identity;
 
 use subtle::Choice;
+use subtle::ConditionallyNegatable;
 use subtle::ConditionallySelectable;
 use subtle::ConstantTimeEq;
 
+use edwards::EdwardsPoint;
 use montgomery::MontgomeryPoint;
 
 use zeroize::Zeroize;
 
+#[cfg(feature = "sec1")]
+use sec1::consts::U32;
+#[cfg(feature = "sec1")]
+use sec1::point::Coordinates;
+#[cfg(feature = "sec1")]
+use sec1::EncodedPoint;
+
 // 'a' parameter for Wei25519
 // https://datatracker.ietf.org/doc/html/draft-ietf-lwig-curve-representations-23#appendix-E.3
 const WEI25519_A: [u8; 32] = [
     0x44, 0xa1, 0x14, 0x49, 0x98, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0x2a,
 ];
 
+// 'b' parameter for Wei25519
+// https://datatracker.ietf.org/doc/html/draft-ietf-lwig-curve-representations-23#appendix-E.3
+const WEI25519_B: [u8; 32] = [
+    0x64, 0xc8, 0x10, 0x77, 0x9c, 0x5e, 0x0b, 0x26, 0xb4, 0x97, 0xd0, 0x5e, 0x42, 0x7b, 0x09, 0xed, 0x25, 0xb4, 0x97, 0xd0, 0x5e, 0x42, 0x7b, 0x09, 0xed, 0x25, 0xb4, 0x97, 0xd0, 0x5e, 0x42, 0x7b,
+];
+
 /// https://datatracker.ietf.org/doc/html/draft-ietf-lwig-curve-representations-23#appendix-E.2
 const DELTA: [u8; 32] = [
     0x51, 0x24, 0xad, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0x2a,
 ];
 
+/// The square root of -486664 used by the birational map between edwards25519 and
+/// Curve25519, chosen such that the ed25519 base point maps to the X25519 base point
+/// https://www.rfc-editor.org/rfc/rfc7748#section-4.1
+const SQRT_M486664: [u8; 32] = [
+    0xe7, 0x81, 0xba, 0x00, 0x55, 0xfb, 0x91, 0x33, 0x7d, 0xe5, 0x82, 0xb4, 0x2e, 0x2c, 0x5e, 0x3a, 0x81, 0xb0, 0x03, 0xfc, 0x23, 0xf7, 0x84, 0x2d, 0x44, 0xf9, 0x5f, 0x9f, 0x0b, 0x12, 0xd9, 0x70,
+];
+
 /// u-coordinate for the X22159 base point on the Montgomery form of Curve25519
 pub const X25519_BASEPOINT_U: [u8; 32] = crate::constants::X25519_BASEPOINT.0;
 
@@ -43,10 +65,39 @@ pub const WEI25519_G_X: [u8; 32] = [
 /// y-coordinate for the X22159 base point on Wei25519
 pub const WEI25519_G_Y: [u8; 32] = X25519_BASEPOINT_V;
 
+/// A guard which, with the `no_panic` feature, fails to link unless the optimizer removes
+/// its destructor, i.e. proves that the code between its creation and [`NoPanic::disarm`]
+/// never unwinds.
+///
+/// The proof needs an optimized build with LTO to see through calls into other crates.
+/// Without the feature the guard does nothing.
+struct NoPanic;
+
+impl NoPanic {
+    #[inline(always)]
+    fn disarm(self) {
+        #[cfg(feature = "no_panic")]
+        core::mem::forget(self);
+    }
+}
+
+#[cfg(feature = "no_panic")]
+impl Drop for NoPanic {
+    #[inline(always)]
+    fn drop(&mut self) {
+        extern "C" {
+            #[link_name = "\n\nERROR: a WeierstrassPoint operation checked by the `no_panic` feature may panic\n\n"]
+            fn weierstrass_may_panic() -> !;
+        }
+        unsafe { weierstrass_may_panic() }
+    }
+}
+
 /// Holds the u-coordinate and v-coordinate of a point on the Weierstrass form of Curve25519.
 /// 
 /// Note: all bytes are in Montgomery convention order
 #[derive(Copy, Clone, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WeierstrassPoint {
     /// x-coordinate in LE
     pub x: [u8; 32],
@@ -56,16 +107,8 @@ pub struct WeierstrassPoint {
 }
 
 impl From<[u8; 64]> for WeierstrassPoint {
-    #[allow(clippy::manual_memcpy)]
     fn from(bytes: [u8; 64]) -> WeierstrassPoint {
-        let mut x = [0; 32];
-        let mut y = [0; 32];
-        for i in 0..32 {
-            x[i] = bytes[i];
-            y[i] = bytes[i + 32];
-        }
-
-        WeierstrassPoint { x, y, }
+        WeierstrassPoint::from_bytes(&bytes)
     }
 }
 
@@ -100,17 +143,77 @@ impl Zeroize for WeierstrassPoint {
 }
 
 impl WeierstrassPoint {
+    /// Construct a `WeierstrassPoint` from the array of bytes of [`WeierstrassPoint::to_bytes`].
+    ///
+    /// This is a `const fn`, so fixed points can be computed at compile time.  The point is not
+    /// validated.
+    pub const fn from_bytes(bytes: &[u8; 64]) -> WeierstrassPoint {
+        let mut x = [0; 32];
+        let mut y = [0; 32];
+        let mut i = 0;
+        while i < 32 {
+            x[i] = bytes[i];
+            y[i] = bytes[i + 32];
+            i += 1;
+        }
+
+        WeierstrassPoint { x, y, }
+    }
+
     /// Convert this `WeierstrassPoint` to an array of bytes.
-    #[allow(clippy::manual_memcpy)]
-    pub fn to_bytes(&self) -> [u8; 64] {
+    pub const fn to_bytes(&self) -> [u8; 64] {
         let mut b = [0; 64];
-        for i in 0..32 {
+        let mut i = 0;
+        while i < 32 {
             b[i] = self.x[i];
             b[i + 32] = self.y[i];
+            i += 1;
         }
         b
     }
 
+    /// Return this point with both coordinates in their canonical (reduced mod p) encoding.
+    pub fn canonical(&self) -> WeierstrassPoint {
+        WeierstrassPoint {
+            x: FieldElement::from_bytes(&self.x).to_bytes(),
+            y: FieldElement::from_bytes(&self.y).to_bytes(),
+        }
+    }
+
+    /// Check whether the canonical y-coordinate of this point is odd.
+    pub fn y_is_odd(&self) -> bool {
+        FieldElement::from_bytes(&self.y).is_negative().into()
+    }
+
+    /// Recover the point on Wei25519 with the x-coordinate `x` and a y-coordinate of the
+    /// given parity.
+    ///
+    /// Returns `None` if `x` is not canonical, is not the x-coordinate of a point on Wei25519,
+    /// or if the only such y-coordinate is zero and `y_is_odd` is set.
+    pub fn decompress(x: &[u8; 32], y_is_odd: bool) -> Option<WeierstrassPoint> {
+        let x_fe = FieldElement::from_bytes(x);
+        if x_fe.to_bytes() != *x {
+            return None;
+        }
+
+        let a = FieldElement::from_bytes(&WEI25519_A);
+        let b = FieldElement::from_bytes(&WEI25519_B);
+        let rhs = &(&(&x_fe.square() + &a) * &x_fe) + &b;
+
+        // sqrt_ratio_i returns the nonnegative (even) square root
+        let (is_square, mut y) = FieldElement::sqrt_ratio_i(&rhs, &FieldElement::one());
+        y.conditional_negate(Choice::from(y_is_odd as u8));
+
+        if !bool::from(is_square) || bool::from(y.is_negative()) != y_is_odd {
+            return None;
+        }
+
+        Some(WeierstrassPoint {
+            x: *x,
+            y: y.to_bytes(),
+        })
+    }
+
     fn x_ct_eq(&self, other: &Self) -> Choice {
         FieldElement::from_bytes(&self.x)
             .ct_eq(&FieldElement::from_bytes(&other.x))
@@ -128,12 +231,51 @@ impl WeierstrassPoint {
         choice
     }
 
+    /// Check in constant time whether this point satisfies the Wei25519 curve equation
+    /// y^2 = x^3 + a*x + b (the point at infinity is considered to be on the curve).
+    ///
+    /// This can be used to detect faults during a scalar multiplication, since a faulty
+    /// intermediate result almost certainly leaves the curve.
+    pub fn is_on_curve(&self) -> bool {
+        let x = FieldElement::from_bytes(&self.x);
+        let y = FieldElement::from_bytes(&self.y);
+        let a = FieldElement::from_bytes(&WEI25519_A);
+        let b = FieldElement::from_bytes(&WEI25519_B);
+
+        // x^3 + a*x + b = (x^2 + a)*x + b
+        let rhs = &(&(&x.square() + &a) * &x) + &b;
+
+        let on_curve = y.square().ct_eq(&rhs) | self.at_infinity();
+        on_curve.into()
+    }
+
+    /// Convert a point on edwards25519 (e.g. an ed25519 public key) to the corresponding
+    /// `WeierstrassPoint`.
+    ///
+    /// Like [`EdwardsPoint::to_montgomery`], both the identity and the point of order 2 map to
+    /// u = 0, which [`WeierstrassPoint::from_montgomery`] treats as the point at infinity.
+    pub fn from_edwards(point: &EdwardsPoint) -> WeierstrassPoint {
+        // https://www.rfc-editor.org/rfc/rfc7748#section-4.1
+        // (x, y)_E => (u, v)_M = ((1+y)/(1-y), sqrt(-486664)*u/x) with x = X/Z, y = Y/Z
+
+        let c = FieldElement::from_bytes(&SQRT_M486664);
+        let zy_sum = &point.Z + &point.Y;
+        let zy_diff = &point.Z - &point.Y;
+
+        let u = &zy_sum * &zy_diff.invert();
+        let v = &(&c * &u) * &(&point.Z * &point.X.invert());
+
+        WeierstrassPoint::from_montgomery(u.to_bytes(), v.to_bytes())
+    }
+
     /// Convert a point (u, v) on the Montgomery form of Curve25519 as `WeierstrassPoint`
     pub fn from_montgomery(u: [u8; 32], v: [u8; 32]) -> WeierstrassPoint {
         // https://datatracker.ietf.org/doc/html/draft-ietf-lwig-curve-representations-23#appendix-D.2
         // (u, v)_M => ((u + A/3)/B, v/B)_W
+        let guard = NoPanic;
 
         if u == [0; 32] {
+            guard.disarm();
             return WeierstrassPoint { x: [0; 32], y: v }
         }
 
@@ -141,6 +283,7 @@ impl WeierstrassPoint {
         let delta = FieldElement::from_bytes(&DELTA);
         let x = &u + &delta;
 
+        guard.disarm();
         WeierstrassPoint {
             x: x.to_bytes(),
             y: v,
@@ -151,8 +294,10 @@ impl WeierstrassPoint {
     pub fn into_montgomery(&self) -> ([u8; 32], [u8; 32]) {
         // Inverse mapping: https://datatracker.ietf.org/doc/html/draft-ietf-lwig-curve-representations-23#appendix-E.2
         // (x, y)_W = (x - A/3, y)_M
+        let guard = NoPanic;
 
         if self.x == [0; 32] {
+            guard.disarm();
             return (self.x, self.y)
         }
 
@@ -160,6 +305,7 @@ impl WeierstrassPoint {
         let delta = FieldElement::from_bytes(&DELTA);
         let u = &x - &delta;
 
+        guard.disarm();
         (u.to_bytes(), self.y)
     }
 
@@ -171,7 +317,51 @@ impl WeierstrassPoint {
 
     /// Constant time (non-jacobian) short-Weierstrass doubling
     pub fn double(&self) -> WeierstrassPoint {
-        *self + *self
+        self + self
+    }
+
+    /// Constant time doubling of this point in place, see [`WeierstrassPoint::double`].
+    ///
+    /// The result is written straight into the coordinates of `self` and every intermediate
+    /// value is wiped, so no copy of a secret-dependent point is left behind.
+    pub fn double_in_place(&mut self) {
+        let mut x1 = FieldElement::from_bytes(&self.x);
+        let mut y1 = FieldElement::from_bytes(&self.y);
+        let a = FieldElement::from_bytes(&WEI25519_A);
+
+        // s = (3*x1^2+a)/(2*y1)
+        let mut x1s = x1.square();
+        let mut x1s2 = &x1s + &x1s;
+        let mut x1s3 = &x1s2 + &x1s;
+        let mut s_num = &x1s3 + &a;
+        let mut y1d = &y1 + &y1;
+        let mut s_den = y1d.invert();
+        let mut s = &s_num * &s_den;
+
+        // x3 = s^2-2*x1
+        let mut s2 = s.square();
+        let mut s2x = &s2 - &x1;
+        let mut x3 = &s2x - &x1;
+
+        // y3 = s*(x1-x3)-y1
+        let mut dx = &x1 - &x3;
+        let mut sdx = &s * &dx;
+        let mut y3 = &sdx - &y1;
+
+        // if y1 = 0: return 0, which covers the point at infinity and the points of order 2
+        let y1_zero = y1.ct_eq(&FieldElement::zero());
+        x3.conditional_assign(&FieldElement::zero(), y1_zero);
+        y3.conditional_assign(&FieldElement::zero(), y1_zero);
+
+        self.x = x3.to_bytes();
+        self.y = y3.to_bytes();
+
+        for fe in [
+            &mut x1, &mut y1, &mut x1s, &mut x1s2, &mut x1s3, &mut s_num, &mut y1d, &mut s_den,
+            &mut s, &mut s2, &mut s2x, &mut x3, &mut dx, &mut sdx, &mut y3,
+        ].iter_mut() {
+            fe.zeroize();
+        }
     }
 }
 
@@ -190,56 +380,86 @@ impl ConstantTimeEq for WeierstrassPoint {
 
 impl ConditionallySelectable for WeierstrassPoint {
     fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
-        WeierstrassPoint {
-            x: FieldElement::conditional_select(
-                &FieldElement::from_bytes(&a.x),
-                &FieldElement::from_bytes(&b.x),
-                choice,
-            ).to_bytes(),
-            y: FieldElement::conditional_select(
-                &FieldElement::from_bytes(&a.y),
-                &FieldElement::from_bytes(&b.y),
-                choice,
-            ).to_bytes(),
+        let mut a_x = FieldElement::from_bytes(&a.x);
+        let mut a_y = FieldElement::from_bytes(&a.y);
+        let mut b_x = FieldElement::from_bytes(&b.x);
+        let mut b_y = FieldElement::from_bytes(&b.y);
+        let mut x = FieldElement::conditional_select(&a_x, &b_x, choice);
+        let mut y = FieldElement::conditional_select(&a_y, &b_y, choice);
+
+        let selected = WeierstrassPoint {
+            x: x.to_bytes(),
+            y: y.to_bytes(),
+        };
+
+        for fe in [&mut a_x, &mut a_y, &mut b_x, &mut b_y, &mut x, &mut y].iter_mut() {
+            fe.zeroize();
         }
+
+        selected
     }
 }
 
-impl Add for WeierstrassPoint {
+impl<'b> Add<&'b WeierstrassPoint> for &WeierstrassPoint {
     type Output = WeierstrassPoint;
 
-    /// Constant time (non-jacobian) short-Weierstrass combined affine addition and doubling
-    fn add(self, rhs: Self) -> WeierstrassPoint {
+    /// Constant time (non-jacobian) short-Weierstrass combined affine addition and doubling,
+    /// see [`AddAssign`].
+    fn add(self, rhs: &'b WeierstrassPoint) -> WeierstrassPoint {
+        let mut sum = *self;
+        sum += rhs;
+        sum
+    }
+}
+
+define_add_variants!(LHS = WeierstrassPoint, RHS = WeierstrassPoint, Output = WeierstrassPoint);
+
+impl<'b> AddAssign<&'b WeierstrassPoint> for WeierstrassPoint {
+    /// Constant time (non-jacobian) short-Weierstrass combined affine addition and doubling,
+    /// writing the sum straight into the coordinates of `self`.
+    fn add_assign(&mut self, rhs: &'b WeierstrassPoint) {
         // Formulas for affine addition/doubling: (https://www.hyperelliptic.org/EFD/g1p/auto-shortw.html)
         // Note: Our usage of this function does not require efficiency, instead constant-time execution
+        let guard = NoPanic;
 
-        let x1 = FieldElement::from_bytes(&self.x);
-        let y1 = FieldElement::from_bytes(&self.y);
+        let mut x1 = FieldElement::from_bytes(&self.x);
+        let mut y1 = FieldElement::from_bytes(&self.y);
 
-        let x2 = FieldElement::from_bytes(&rhs.x);
-        let y2 = FieldElement::from_bytes(&rhs.y);
+        let mut x2 = FieldElement::from_bytes(&rhs.x);
+        let mut y2 = FieldElement::from_bytes(&rhs.y);
 
-        let x1s = x1.square();
-        let x1s3 = &(&x1s + &x1s) + &x1s;
+        let mut x1s = x1.square();
+        let mut x1s2 = &x1s + &x1s;
+        let mut x1s3 = &x1s2 + &x1s;
         let a = FieldElement::from_bytes(&WEI25519_A);
 
         // s = (3*x1^2+a)/(2*y1)
-        let s = &(&x1s3 + &a) * &(&y1 + &y1).invert();
+        let mut s_num = &x1s3 + &a;
+        let mut y1d = &y1 + &y1;
+        let mut s_den = y1d.invert();
+        let mut s = &s_num * &s_den;
 
         // r = (y2-y1)/(x2-x1)
-        let r = &(&y2 - &y1) * &(&x2 - &x1).invert();
+        let mut r_num = &y2 - &y1;
+        let mut dx21 = &x2 - &x1;
+        let mut r_den = dx21.invert();
+        let mut r = &r_num * &r_den;
 
         // if x1 = x2 AND y1 = y2: u:=s else: u:=r
-        let mut x_eq = self.x_ct_eq(&rhs);
-        let y_eq = self.y_ct_eq(&rhs);
+        let mut x_eq = self.x_ct_eq(rhs);
+        let y_eq = self.y_ct_eq(rhs);
         let mut u = r;
         u.conditional_assign(&s, x_eq & y_eq);
 
         // x3 = u^2-x1-x2
-        let mut x3 = &(&u.square() - &x1) - &x2;
+        let mut u2 = u.square();
+        let mut u2x = &u2 - &x1;
+        let mut x3 = &u2x - &x2;
 
         // y3 = u*(x1-x3)-y1
-        let mut y3 = &(&u * &(&x1 - &x3)) - &y1;
+        let mut dx = &x1 - &x3;
+        let mut udx = &u * &dx;
+        let mut y3 = &udx - &y1;
 
         // if (x1, y1) = 0: return (x2, y2)
         let at_infinity1 = self.at_infinity();
@@ -251,24 +471,30 @@ impl Add for WeierstrassPoint {
         x3.conditional_assign(&x1, at_infinity2);
         y3.conditional_assign(&y1, at_infinity2);
 
-        // if x1 = x2 AND y1 != y2: return 0
-        x_eq &= !at_infinity1 & !at_infinity2 & !y_eq;
+        // if x1 = x2 AND (y1 != y2 OR y1 = 0): return 0, which includes doubling a point of order 2
+        let y1_zero = y1.ct_eq(&FieldElement::zero());
+        x_eq &= !at_infinity1 & !at_infinity2 & (!y_eq | y1_zero);
         x3.conditional_assign(&FieldElement::zero(), x_eq);
         y3.conditional_assign(&FieldElement::zero(), x_eq);
 
-        WeierstrassPoint {
-            x: x3.to_bytes(),
-            y: y3.to_bytes(),
+        self.x = x3.to_bytes();
+        self.y = y3.to_bytes();
+
+        // The inputs may be derived from secret scalars, so wipe every intermediate value
+        for fe in [
+            &mut x1, &mut y1, &mut x2, &mut y2, &mut x1s, &mut x1s2, &mut x1s3, &mut s_num,
+            &mut y1d, &mut s_den, &mut s, &mut r_num, &mut dx21, &mut r_den, &mut r, &mut u,
+            &mut u2, &mut u2x, &mut dx, &mut udx, &mut x3, &mut y3,
+        ].iter_mut() {
+            fe.zeroize();
         }
-    }
-}
 
-impl AddAssign for WeierstrassPoint {
-    fn add_assign(&mut self, rhs: Self) {
-        *self = *self + rhs;
+        guard.disarm();
     }
 }
 
+define_add_assign_variants!(LHS = WeierstrassPoint, RHS = WeierstrassPoint);
+
 define_mul_assign_variants!(LHS = WeierstrassPoint, RHS = Scalar);
 
 define_mul_variants!(LHS = WeierstrassPoint, RHS = Scalar, Output = WeierstrassPoint);
@@ -279,23 +505,33 @@ impl<'a, 'b> Mul<&'b Scalar> for &'a WeierstrassPoint {
 
     #[allow(clippy::needless_range_loop)]
     fn mul(self, scalar: &'b Scalar) -> WeierstrassPoint {
+        let guard = NoPanic;
         let mut acc = WeierstrassPoint::default();
         let mut p = *self;
+        let mut a = WeierstrassPoint::default();
 
-        let bits: [i8; 256] = scalar.bits();
+        let mut bits: [i8; 256] = scalar.bits();
 
         for i in 0..255 {
             let choice: u8 = bits[i] as u8;
-            let mut a = WeierstrassPoint::default();
 
             debug_assert!(choice == 0 || choice == 1);
 
-            a.conditional_assign(&p, choice.into());
-            acc += a;
+            // Select p or the identity (0, 0) into the same storage in every iteration
+            let choice = Choice::from(choice);
+            for (dst, src) in a.x.iter_mut().chain(a.y.iter_mut()).zip(p.x.iter().chain(p.y.iter())) {
+                *dst = u8::conditional_select(&0, src, choice);
+            }
+            acc += &a;
 
-            p = p.double();
+            p.double_in_place();
         }
 
+        bits[..].zeroize();
+        p.zeroize();
+        a.zeroize();
+
+        guard.disarm();
         acc
     }
 }
@@ -314,6 +550,254 @@ impl<'a, 'b> Mul<&'b WeierstrassPoint> for &'a Scalar {
     }
 }
 
+/// A point in the prime-order subgroup of Wei25519.
+///
+/// Wei25519 has cofactor 8, so a point decoded from untrusted bytes may carry a small-order
+/// component.  A `PrimeOrderPoint` can only be constructed by clearing the cofactor
+/// ([`PrimeOrderPoint::clear_cofactor`]) or by checking subgroup membership
+/// ([`PrimeOrderPoint::from_point`], [`PrimeOrderPoint::from_bytes`]), so protocols built on
+/// it work in a group of prime order `l`.
+///
+/// Unlike Ristretto this is a subgroup and not a quotient group: every group element has a
+/// single, canonical encoding, which is the SEC1 compressed encoding of the point.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct PrimeOrderPoint(WeierstrassPoint);
+
+impl PrimeOrderPoint {
+    /// The generator of the prime-order subgroup, i.e. the Wei25519 base point.
+    pub const fn generator() -> PrimeOrderPoint {
+        PrimeOrderPoint(crate::constants::WEI25519_BASEPOINT)
+    }
+
+    /// Map any point on Wei25519 into the prime-order subgroup by multiplying it by the
+    /// cofactor 8.
+    ///
+    /// Returns `None` if `point` does not lie on Wei25519 or is of small order, i.e. if the
+    /// result is the point at infinity.
+    pub fn clear_cofactor(point: &WeierstrassPoint) -> Option<PrimeOrderPoint> {
+        let mut p = *point;
+        p.double_in_place();
+        p.double_in_place();
+        p.double_in_place();
+
+        if point.is_on_curve() && !bool::from(p.at_infinity()) {
+            Some(PrimeOrderPoint(p))
+        } else {
+            None
+        }
+    }
+
+    /// Check that `point` lies on Wei25519 and in its prime-order subgroup.
+    ///
+    /// Returns `None` otherwise.
+    pub fn from_point(point: &WeierstrassPoint) -> Option<PrimeOrderPoint> {
+        let order = point * crate::constants::BASEPOINT_ORDER;
+
+        if point.is_on_curve() && bool::from(order.at_infinity()) {
+            Some(PrimeOrderPoint(point.canonical()))
+        } else {
+            None
+        }
+    }
+
+    /// Encode this point as the SEC1 compressed encoding of the point, i.e. a `0x02` (even y)
+    /// or `0x03` (odd y) tag followed by the big-endian x-coordinate, or all zeros for the
+    /// identity.
+    pub fn to_bytes(&self) -> [u8; 33] {
+        let mut bytes = [0u8; 33];
+        if bool::from(self.0.at_infinity()) {
+            return bytes;
+        }
+
+        let p = self.0.canonical();
+        bytes[0] = 0x02 | p.y_is_odd() as u8;
+        bytes[1..].copy_from_slice(&p.x);
+        bytes[1..].reverse();
+        bytes
+    }
+
+    /// Decode a point from its canonical encoding, see [`PrimeOrderPoint::to_bytes`].
+    ///
+    /// Returns `None` if the encoding is not canonical or the point is not in the prime-order
+    /// subgroup.
+    pub fn from_bytes(bytes: &[u8; 33]) -> Option<PrimeOrderPoint> {
+        let y_is_odd = match bytes[0] {
+            0x00 if bytes[1..].iter().all(|b| *b == 0) => return Some(PrimeOrderPoint::identity()),
+            0x02 => false,
+            0x03 => true,
+            _ => return None,
+        };
+
+        let mut x = [0u8; 32];
+        x.copy_from_slice(&bytes[1..]);
+        x.reverse();
+
+        PrimeOrderPoint::from_point(&WeierstrassPoint::decompress(&x, y_is_odd)?)
+    }
+
+    /// View this point as a [`WeierstrassPoint`].
+    pub fn as_point(&self) -> &WeierstrassPoint {
+        &self.0
+    }
+}
+
+impl Identity for PrimeOrderPoint {
+    fn identity() -> PrimeOrderPoint {
+        PrimeOrderPoint(WeierstrassPoint::identity())
+    }
+}
+
+impl Default for PrimeOrderPoint {
+    fn default() -> PrimeOrderPoint {
+        PrimeOrderPoint::identity()
+    }
+}
+
+impl ConstantTimeEq for PrimeOrderPoint {
+    fn ct_eq(&self, other: &PrimeOrderPoint) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl ConditionallySelectable for PrimeOrderPoint {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        PrimeOrderPoint(WeierstrassPoint::conditional_select(&a.0, &b.0, choice))
+    }
+}
+
+impl Zeroize for PrimeOrderPoint {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl From<PrimeOrderPoint> for WeierstrassPoint {
+    fn from(point: PrimeOrderPoint) -> WeierstrassPoint {
+        point.0
+    }
+}
+
+impl<'b> Add<&'b PrimeOrderPoint> for &PrimeOrderPoint {
+    type Output = PrimeOrderPoint;
+
+    fn add(self, rhs: &'b PrimeOrderPoint) -> PrimeOrderPoint {
+        PrimeOrderPoint(self.0 + rhs.0)
+    }
+}
+
+define_add_variants!(LHS = PrimeOrderPoint, RHS = PrimeOrderPoint, Output = PrimeOrderPoint);
+
+impl<'b> AddAssign<&'b PrimeOrderPoint> for PrimeOrderPoint {
+    fn add_assign(&mut self, rhs: &'b PrimeOrderPoint) {
+        self.0 += &rhs.0;
+    }
+}
+
+define_add_assign_variants!(LHS = PrimeOrderPoint, RHS = PrimeOrderPoint);
+
+impl Neg for &PrimeOrderPoint {
+    type Output = PrimeOrderPoint;
+
+    fn neg(self) -> PrimeOrderPoint {
+        let mut y = FieldElement::from_bytes(&self.0.y);
+        y.conditional_negate(Choice::from(1));
+
+        PrimeOrderPoint(WeierstrassPoint {
+            x: self.0.x,
+            y: y.to_bytes(),
+        })
+    }
+}
+
+impl Neg for PrimeOrderPoint {
+    type Output = PrimeOrderPoint;
+
+    fn neg(self) -> PrimeOrderPoint {
+        -&self
+    }
+}
+
+impl<'b> Sub<&'b PrimeOrderPoint> for &PrimeOrderPoint {
+    type Output = PrimeOrderPoint;
+
+    fn sub(self, rhs: &'b PrimeOrderPoint) -> PrimeOrderPoint {
+        self + (-rhs)
+    }
+}
+
+define_sub_variants!(LHS = PrimeOrderPoint, RHS = PrimeOrderPoint, Output = PrimeOrderPoint);
+
+impl<'b> Mul<&'b Scalar> for &PrimeOrderPoint {
+    type Output = PrimeOrderPoint;
+
+    fn mul(self, scalar: &'b Scalar) -> PrimeOrderPoint {
+        PrimeOrderPoint(self.0 * scalar)
+    }
+}
+
+impl<'b> Mul<&'b PrimeOrderPoint> for &Scalar {
+    type Output = PrimeOrderPoint;
+
+    fn mul(self, point: &'b PrimeOrderPoint) -> PrimeOrderPoint {
+        point * self
+    }
+}
+
+define_mul_variants!(LHS = PrimeOrderPoint, RHS = Scalar, Output = PrimeOrderPoint);
+define_mul_variants!(LHS = Scalar, RHS = PrimeOrderPoint, Output = PrimeOrderPoint);
+
+#[cfg(feature = "sec1")]
+/// SEC1 point encoding
+impl WeierstrassPoint {
+    /// Encode this point in the SEC1 `Elliptic-Curve-Point-to-Octet-String` format, with
+    /// big-endian coordinates.
+    pub fn to_encoded_point(&self, compress: bool) -> EncodedPoint<U32> {
+        if self.at_infinity().into() {
+            return EncodedPoint::identity();
+        }
+
+        let p = self.canonical();
+        let mut x = p.x;
+        let mut y = p.y;
+        x.reverse();
+        y.reverse();
+
+        EncodedPoint::from_affine_coordinates(x[..].into(), y[..].into(), compress)
+    }
+
+    /// Decode a point from the SEC1 `Octet-String-to-Elliptic-Curve-Point` format.
+    ///
+    /// Returns `None` if a coordinate is not canonical, if the point is not on Wei25519 or
+    /// if it uses the non-standard compact encoding.
+    pub fn from_encoded_point(encoded: &EncodedPoint<U32>) -> Option<WeierstrassPoint> {
+        match encoded.coordinates() {
+            Coordinates::Identity => Some(WeierstrassPoint::default()),
+            Coordinates::Compact { .. } => None,
+            Coordinates::Compressed { x, y_is_odd } => {
+                let mut x_le = [0u8; 32];
+                x_le.copy_from_slice(x);
+                x_le.reverse();
+
+                WeierstrassPoint::decompress(&x_le, y_is_odd)
+            }
+            Coordinates::Uncompressed { x, y } => {
+                let mut p = WeierstrassPoint::default();
+                p.x.copy_from_slice(x);
+                p.y.copy_from_slice(y);
+                p.x.reverse();
+                p.y.reverse();
+
+                let is_canonical = p.canonical().to_bytes() == p.to_bytes();
+                if is_canonical && !bool::from(p.at_infinity()) && p.is_on_curve() {
+                    Some(p)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
 #[cfg(feature = "weierstrass_non_constant_time")]
 /// Non-constant time group operations
 impl WeierstrassPoint {
@@ -451,6 +935,172 @@ mod test {
         )
     }
 
+    #[test]
+    fn basepoint_is_on_curve() {
+        assert!(crate::constants::WEI25519_BASEPOINT.is_on_curve());
+        assert!(WeierstrassPoint::default().is_on_curve());
+
+        let mut y = FieldElement::from_bytes(&WEI25519_G_Y);
+        y = &y + &FieldElement::one();
+        let off_curve = WeierstrassPoint {
+            x: WEI25519_G_X,
+            y: y.to_bytes(),
+        };
+        assert!(!off_curve.is_on_curve());
+    }
+
+    #[test]
+    fn scalar_mul_stays_on_curve() {
+        let mut csprng: OsRng = OsRng;
+
+        let s: Scalar = Scalar::random(&mut csprng);
+        let p = s * crate::constants::WEI25519_BASEPOINT;
+
+        assert!(p.is_on_curve());
+    }
+
+    #[test]
+    fn const_constructors() {
+        const G_BYTES: [u8; 64] = crate::constants::WEI25519_BASEPOINT.to_bytes();
+        const G: WeierstrassPoint = WeierstrassPoint::from_bytes(&G_BYTES);
+        const GENERATOR: PrimeOrderPoint = PrimeOrderPoint::generator();
+
+        assert_eq!(G, crate::constants::WEI25519_BASEPOINT);
+        assert_eq!(WeierstrassPoint::from(G_BYTES), G);
+        assert_eq!(*GENERATOR.as_point(), G);
+    }
+
+    #[test]
+    fn in_place_arithmetic() {
+        let mut csprng: OsRng = OsRng;
+
+        let s: Scalar = Scalar::random(&mut csprng);
+        let p = s * crate::constants::WEI25519_BASEPOINT;
+
+        let mut q = p;
+        q.double_in_place();
+        assert_eq!(q, p + p);
+
+        q += &p;
+        assert_eq!(q, Scalar::from(3u64) * p);
+
+        let mut identity = WeierstrassPoint::identity();
+        identity.double_in_place();
+        assert_eq!(identity, WeierstrassPoint::identity());
+
+        let mut torsion = WeierstrassPoint::decompress(&DELTA, false).unwrap();
+        torsion.double_in_place();
+        assert_eq!(torsion, WeierstrassPoint::identity());
+    }
+
+    #[test]
+    fn from_edwards_is_a_homomorphism() {
+        let mut csprng: OsRng = OsRng;
+
+        let basepoint = WeierstrassPoint::from_edwards(&crate::constants::ED25519_BASEPOINT_POINT);
+        assert_eq!(basepoint, crate::constants::WEI25519_BASEPOINT);
+
+        let s: Scalar = Scalar::random(&mut csprng);
+        let edwards = &s * &crate::constants::ED25519_BASEPOINT_TABLE;
+        assert_eq!(WeierstrassPoint::from_edwards(&edwards), s * crate::constants::WEI25519_BASEPOINT);
+        assert_eq!(
+            WeierstrassPoint::from_edwards(&EdwardsPoint::identity()),
+            WeierstrassPoint::identity()
+        );
+    }
+
+    #[test]
+    fn prime_order_point() {
+        let mut csprng: OsRng = OsRng;
+
+        let g = PrimeOrderPoint::generator();
+        let a = g * Scalar::random(&mut csprng);
+        let b = g * Scalar::random(&mut csprng);
+
+        assert_eq!(PrimeOrderPoint::from_bytes(&a.to_bytes()), Some(a));
+        assert_eq!(PrimeOrderPoint::from_bytes(&[0u8; 33]), Some(PrimeOrderPoint::identity()));
+        assert_eq!((a + b) - b, a);
+        assert_eq!(a - a, PrimeOrderPoint::identity());
+
+        // (DELTA, 0) is the image of the Montgomery point (0, 0) of order 2
+        let torsion = WeierstrassPoint::decompress(&DELTA, false).unwrap();
+        assert_eq!(torsion + torsion, WeierstrassPoint::identity());
+        assert_eq!(PrimeOrderPoint::clear_cofactor(&torsion), None);
+
+        let mixed = *a.as_point() + torsion;
+        assert_eq!(PrimeOrderPoint::from_point(&mixed), None);
+        assert_eq!(PrimeOrderPoint::clear_cofactor(&mixed), Some(a * Scalar::from(8u64)));
+    }
+
+    #[test]
+    fn clear_cofactor_rejects_off_curve_points() {
+        let mut one = [0u8; 32]; one[0] = 1;
+        let off_curve = WeierstrassPoint { x: one, y: one };
+
+        assert!(!off_curve.is_on_curve());
+        assert_eq!(PrimeOrderPoint::clear_cofactor(&off_curve), None);
+        assert_eq!(PrimeOrderPoint::clear_cofactor(&WeierstrassPoint::identity()), None);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_bincode_basepoint_roundtrip() {
+        use bincode;
+
+        let basepoint = crate::constants::WEI25519_BASEPOINT;
+        let encoded = bincode::serialize(&basepoint).unwrap();
+        let decoded: WeierstrassPoint = bincode::deserialize(&encoded).unwrap();
+
+        assert_eq!(encoded.len(), 64);
+        assert_eq!(decoded, basepoint);
+
+        let bp: WeierstrassPoint = bincode::deserialize(&basepoint.to_bytes()).unwrap();
+        assert_eq!(bp, basepoint);
+    }
+
+    #[test]
+    fn decompress_recovers_point() {
+        let mut csprng: OsRng = OsRng;
+
+        let s: Scalar = Scalar::random(&mut csprng);
+        let p = s * crate::constants::WEI25519_BASEPOINT;
+
+        assert_eq!(WeierstrassPoint::decompress(&p.x, p.y_is_odd()), Some(p));
+        assert_eq!(WeierstrassPoint::decompress(&p.x, !p.y_is_odd()), Some(p_neg(&p)));
+        assert_eq!(WeierstrassPoint::decompress(&[0xff; 32], false), None);
+    }
+
+    fn p_neg(p: &WeierstrassPoint) -> WeierstrassPoint {
+        WeierstrassPoint {
+            x: p.x,
+            y: (-&FieldElement::from_bytes(&p.y)).to_bytes(),
+        }
+    }
+
+    #[cfg(feature = "sec1")]
+    #[test]
+    fn sec1_roundtrip() {
+        let mut csprng: OsRng = OsRng;
+
+        let s: Scalar = Scalar::random(&mut csprng);
+        let p = s * crate::constants::WEI25519_BASEPOINT;
+
+        for compress in [false, true] {
+            let encoded = p.to_encoded_point(compress);
+            assert_eq!(encoded.is_compressed(), compress);
+            assert_eq!(WeierstrassPoint::from_encoded_point(&encoded), Some(p));
+        }
+
+        let identity = WeierstrassPoint::default().to_encoded_point(true);
+        assert!(identity.is_identity());
+        assert_eq!(WeierstrassPoint::from_encoded_point(&identity), Some(WeierstrassPoint::default()));
+
+        let mut bytes = p.to_encoded_point(false).as_bytes().to_vec();
+        bytes[64] ^= 1;
+        let off_curve = EncodedPoint::<U32>::from_bytes(&bytes).unwrap();
+        assert_eq!(WeierstrassPoint::from_encoded_point(&off_curve), None);
+    }
+
     #[test]
     fn eq_defined_mod_p() {
         let mut u18_bytes = [0u8; 32]; u18_bytes[0] = 18;
@@ -467,6 +1117,19 @@ mod test {
         assert_eq!(u18, u18_unred);
     }
 
+    #[test]
+    fn canonical_reduces_coordinates() {
+        let mut u18_bytes = [0u8; 32]; u18_bytes[0] = 18;
+
+        let u18_unred = WeierstrassPoint {
+            x: [255; 32],
+            y: [255; 32],
+        };
+
+        assert_eq!(u18_unred.canonical().x, u18_bytes);
+        assert_eq!(u18_unred.canonical().y, u18_bytes);
+    }
+
     #[test]
     fn map_point_at_infinity() {
         let w = WeierstrassPoint::default();
@@ -0,0 +1,223 @@
+//! Key agreement schemes from NIST SP 800-56A over Wei25519
+//!
+//! This implements the One-Pass Unified Model scheme `C(1e, 2s)` and the Full MQV scheme
+//! `C(2e, 2s)` as specified in [SP 800-56A Rev. 3](https://doi.org/10.6028/NIST.SP.800-56Ar3),
+//! using the w25519 [`StaticSecret`], [`EphemeralSecret`] and [`PublicKey`] types.
+//!
+//! As with [`StaticSecret::diffie_hellman`], the resulting shared secrets are encoded as
+//! Montgomery u-coordinates.
+//!
+//! All schemes validate the counterparty's public keys as SP 800-56A requires: a key must be
+//! a point on Wei25519 other than the point at infinity.  Small-order components are removed
+//! by the cofactor, and a shared point at infinity is rejected, so each function returns
+//! `None` instead of a shared secret if validation fails.
+
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::weierstrass::WeierstrassPoint;
+use x25519_dalek::SharedSecret;
+
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::w25519::{EphemeralSecret, PublicKey, StaticSecret};
+
+/// The shared secret `Z = Ze || Zs` of the One-Pass Unified Model scheme.
+///
+/// `Ze` is the shared secret involving the initiator's ephemeral key and `Zs` the shared
+/// secret between both static keys.
+#[derive(Zeroize)]
+#[zeroize(drop)]
+pub struct UnifiedSharedSecret([u8; 64]);
+
+impl UnifiedSharedSecret {
+    fn new(ze: SharedSecret, zs: SharedSecret) -> Self {
+        let mut z = [0u8; 64];
+        z[..32].copy_from_slice(ze.as_bytes());
+        z[32..].copy_from_slice(zs.as_bytes());
+
+        UnifiedSharedSecret(z)
+    }
+
+    /// Convert this shared secret to a byte array.
+    #[inline]
+    pub fn to_bytes(&self) -> [u8; 64] {
+        self.0
+    }
+
+    /// Convert this shared secret to a byte array which is wiped when it is dropped.
+    pub fn to_bytes_zeroizing(&self) -> Zeroizing<[u8; 64]> {
+        Zeroizing::new(self.0)
+    }
+
+    /// View this shared secret as a byte array.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8; 64] {
+        &self.0
+    }
+}
+
+/// One-Pass Unified Model, computed by the initiator `U` which contributes an ephemeral key.
+///
+/// The initiator sends `PublicKey::from(&our_ephemeral)` to the responder, see
+/// [`one_pass_unified_responder`].
+///
+/// Returns `None` if `their_static` is invalid or a shared secret is the point at infinity.
+pub fn one_pass_unified_initiator(
+    our_static: &StaticSecret,
+    our_ephemeral: EphemeralSecret,
+    their_static: &PublicKey,
+) -> Option<UnifiedSharedSecret> {
+    if !their_static.is_valid() {
+        return None;
+    }
+
+    let ze = contributory(our_ephemeral.diffie_hellman_checked(their_static))?;
+    let zs = contributory(our_static.diffie_hellman_checked(their_static))?;
+
+    Some(UnifiedSharedSecret::new(ze, zs))
+}
+
+/// One-Pass Unified Model, computed by the responder `V` which only holds a static key.
+///
+/// Returns `None` if a public key is invalid or a shared secret is the point at infinity.
+pub fn one_pass_unified_responder(
+    our_static: &StaticSecret,
+    their_static: &PublicKey,
+    their_ephemeral: &PublicKey,
+) -> Option<UnifiedSharedSecret> {
+    if !their_static.is_valid() || !their_ephemeral.is_valid() {
+        return None;
+    }
+
+    let ze = contributory(our_static.diffie_hellman_checked(their_ephemeral))?;
+    let zs = contributory(our_static.diffie_hellman_checked(their_static))?;
+
+    Some(UnifiedSharedSecret::new(ze, zs))
+}
+
+/// Full MQV, computed by either party from its own static and ephemeral keys and the
+/// counterparty's static and ephemeral public keys.
+///
+/// Following the ECC MQV primitive, this computes the implicit signature
+/// `s = (de + avf(Qe) * ds) mod n` and the shared point
+/// `P = h * s * (Qe' + avf(Qe') * Qs')` with the cofactor `h = 8`.
+///
+/// Returns `None` if a public key is invalid, or if the shared point is the point at infinity
+/// or, due to a fault, not on Wei25519.
+pub fn mqv(
+    our_static: &StaticSecret,
+    our_ephemeral: EphemeralSecret,
+    their_static: &PublicKey,
+    their_ephemeral: &PublicKey,
+) -> Option<SharedSecret> {
+    if !their_static.is_valid() || !their_ephemeral.is_valid() {
+        return None;
+    }
+
+    let our_ephemeral_public = PublicKey::from(&our_ephemeral);
+
+    let mut implicit_sig =
+        our_ephemeral.0.reduce() + avf(&our_ephemeral_public.0) * our_static.0.reduce();
+
+    let q = their_ephemeral.0 + avf(&their_ephemeral.0) * their_static.0;
+    let p = (implicit_sig * q).double().double().double();
+
+    implicit_sig.zeroize();
+
+    if !p.is_on_curve() {
+        return None;
+    }
+    contributory(Some(SharedSecret::new(p.into_montgomery_compressed())))
+}
+
+/// Reject a missing or non-contributory shared secret, i.e. a shared point at infinity.
+fn contributory(shared: Option<SharedSecret>) -> Option<SharedSecret> {
+    shared.filter(SharedSecret::was_contributory)
+}
+
+/// The associate value function `avf(Q) = (x mod 2^ceil(f/2)) + 2^ceil(f/2)`, where `x` is
+/// the x-coordinate of `Q` and `f = 253` is the bit length of the group order.
+fn avf(q: &WeierstrassPoint) -> Scalar {
+    let x = q.canonical().x;
+
+    let mut bytes = [0u8; 32];
+    bytes[..16].copy_from_slice(&x[..16]);
+    bytes[15] |= 0x80;
+
+    Scalar::from_bits(bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rand_core::OsRng;
+
+    #[test]
+    fn one_pass_unified_agreement() {
+        let csprng: OsRng = OsRng;
+
+        let u_static = StaticSecret::new(csprng);
+        let u_ephemeral = EphemeralSecret::new(csprng);
+        let v_static = StaticSecret::new(csprng);
+
+        let u_static_public = PublicKey::from(&u_static);
+        let u_ephemeral_public = PublicKey::from(&u_ephemeral);
+        let v_static_public = PublicKey::from(&v_static);
+
+        let z_u = one_pass_unified_initiator(&u_static, u_ephemeral, &v_static_public).unwrap();
+        let z_v =
+            one_pass_unified_responder(&v_static, &u_static_public, &u_ephemeral_public).unwrap();
+
+        assert_eq!(z_u.to_bytes(), z_v.to_bytes());
+        assert_eq!(
+            &z_u.as_bytes()[32..],
+            u_static.diffie_hellman(&v_static_public).as_bytes()
+        );
+    }
+
+    #[test]
+    fn mqv_agreement() {
+        let csprng: OsRng = OsRng;
+
+        let u_static = StaticSecret::new(csprng);
+        let u_ephemeral = EphemeralSecret::new(csprng);
+        let v_static = StaticSecret::new(csprng);
+        let v_ephemeral = EphemeralSecret::new(csprng);
+
+        let u_static_public = PublicKey::from(&u_static);
+        let u_ephemeral_public = PublicKey::from(&u_ephemeral);
+        let v_static_public = PublicKey::from(&v_static);
+        let v_ephemeral_public = PublicKey::from(&v_ephemeral);
+
+        let z_u = mqv(&u_static, u_ephemeral, &v_static_public, &v_ephemeral_public).unwrap();
+        let z_v = mqv(&v_static, v_ephemeral, &u_static_public, &u_ephemeral_public).unwrap();
+
+        assert_eq!(z_u.to_bytes(), z_v.to_bytes());
+        assert!(z_u.was_contributory());
+        assert_ne!(
+            z_u.to_bytes(),
+            u_static.diffie_hellman(&v_static_public).to_bytes()
+        );
+    }
+
+    #[test]
+    fn invalid_public_keys_are_rejected() {
+        let csprng: OsRng = OsRng;
+
+        let our_static = StaticSecret::new(csprng);
+        let valid = PublicKey::from(&StaticSecret::new(csprng));
+        let identity = PublicKey::from([0u8; 64]);
+        let mut off_curve_bytes = valid.to_bytes();
+        off_curve_bytes[32] ^= 1;
+        let off_curve = PublicKey::from(off_curve_bytes);
+
+        for invalid in [identity, off_curve].iter() {
+            assert!(one_pass_unified_initiator(&our_static, EphemeralSecret::new(csprng), invalid)
+                .is_none());
+            assert!(one_pass_unified_responder(&our_static, invalid, &valid).is_none());
+            assert!(one_pass_unified_responder(&our_static, &valid, invalid).is_none());
+            assert!(mqv(&our_static, EphemeralSecret::new(csprng), invalid, &valid).is_none());
+            assert!(mqv(&our_static, EphemeralSecret::new(csprng), &valid, invalid).is_none());
+        }
+    }
+}
@@ -0,0 +1,243 @@
+//! COSE_Key ([RFC 9052], [RFC 9053]) encoding of w25519 public keys.
+//!
+//! Wei25519 has no registered entry in the IANA "COSE Elliptic Curves" registry, so keys are
+//! encoded as EC2 keys with the curve identifier [`COSE_CRV_WEI25519`] taken from the
+//! private use range (integers less than -65536). Both sides of an exchange have to agree on
+//! this value out of band.
+//!
+//! Encoding produces the deterministic encoding of RFC 8949 section 4.2.1:
+//!
+//! ```text
+//! {
+//!    1: 2,                // kty: EC2
+//!   -1: -65537,           // crv: Wei25519 (private use)
+//!   -2: h'...',           // x: 32 bytes, big-endian
+//!   -3: h'...',           // y: 32 bytes, big-endian
+//! }
+//! ```
+//!
+//! [RFC 9052]: https://www.rfc-editor.org/rfc/rfc9052#section-7
+//! [RFC 9053]: https://www.rfc-editor.org/rfc/rfc9053#section-7.1.1
+
+use core::convert::TryFrom;
+
+use crate::PublicKey;
+
+/// The COSE curve identifier used for Wei25519 keys.
+pub const COSE_CRV_WEI25519: i64 = -65537;
+
+/// The length of the COSE_Key encoding of a [`PublicKey`].
+pub const COSE_KEY_LEN: usize = 79;
+
+const KTY: i64 = 1;
+const KTY_EC2: i64 = 2;
+const CRV: i64 = -1;
+const X: i64 = -2;
+const Y: i64 = -3;
+const D: i64 = -4;
+
+/// Nesting limit for skipping over unknown parameters, e.g. `key_ops` arrays.
+const MAX_DEPTH: usize = 4;
+
+impl PublicKey {
+    /// Encode this public key as a COSE_Key map, see the [module documentation](crate::cose).
+    pub fn to_cose_key(&self) -> [u8; COSE_KEY_LEN] {
+        let p = self.0.canonical();
+
+        let mut bytes = [0u8; COSE_KEY_LEN];
+        bytes[..10].copy_from_slice(&[0xa4, 0x01, 0x02, 0x20, 0x3a, 0x00, 0x01, 0x00, 0x00, 0x21]);
+        bytes[10..12].copy_from_slice(&[0x58, 0x20]);
+        bytes[12..44].copy_from_slice(&p.x);
+        bytes[12..44].reverse();
+        bytes[44..47].copy_from_slice(&[0x22, 0x58, 0x20]);
+        bytes[47..].copy_from_slice(&p.y);
+        bytes[47..].reverse();
+        bytes
+    }
+
+    /// Decode a public key from a COSE_Key map.
+    ///
+    /// The map must be an EC2 key on [`COSE_CRV_WEI25519`] with both coordinates present.
+    /// Other parameters such as `kid` or `alg` are ignored, while maps carrying a private key
+    /// (`d`) are rejected so that secrets are not silently dropped.
+    ///
+    /// Returns `None` if the input is not such a map, has trailing bytes, or the coordinates
+    /// are not canonical or do not lie on Wei25519.
+    pub fn from_cose_key(bytes: &[u8]) -> Option<PublicKey> {
+        let mut reader = Reader { bytes, pos: 0 };
+
+        let (major, len) = reader.head()?;
+        if major != MAJOR_MAP {
+            return None;
+        }
+
+        let mut kty = None;
+        let mut crv = None;
+        let mut x = None;
+        let mut y = None;
+
+        for _ in 0..len {
+            let duplicate = match reader.int()? {
+                KTY => kty.replace(reader.int()?).is_some(),
+                CRV => crv.replace(reader.int()?).is_some(),
+                X => x.replace(reader.coordinate()?).is_some(),
+                Y => y.replace(reader.coordinate()?).is_some(),
+                D => return None,
+                _ => {
+                    reader.skip(0)?;
+                    false
+                }
+            };
+            // Duplicate labels are invalid in COSE.
+            if duplicate {
+                return None;
+            }
+        }
+
+        if reader.pos != bytes.len() || kty? != KTY_EC2 || crv? != COSE_CRV_WEI25519 {
+            return None;
+        }
+
+        let (mut x, mut y) = (x?, y?);
+        x.reverse();
+        y.reverse();
+
+        PublicKey::from_coordinates(x, y)
+    }
+}
+
+const MAJOR_UINT: u8 = 0;
+const MAJOR_NINT: u8 = 1;
+const MAJOR_BSTR: u8 = 2;
+const MAJOR_TSTR: u8 = 3;
+const MAJOR_ARRAY: u8 = 4;
+const MAJOR_MAP: u8 = 5;
+const MAJOR_TAG: u8 = 6;
+
+/// A minimal reader for the definite-length CBOR subset used by COSE_Key.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        let end = self.pos.checked_add(n)?;
+        let taken = self.bytes.get(self.pos..end)?;
+        self.pos = end;
+        Some(taken)
+    }
+
+    /// Read the major type and argument of the next data item.
+    fn head(&mut self) -> Option<(u8, u64)> {
+        let initial = self.take(1)?[0];
+        let major = initial >> 5;
+        let argument = match initial & 0x1f {
+            info @ 0..=23 => info as u64,
+            24 => self.take(1)?[0] as u64,
+            25 => self.take(2)?.iter().fold(0, |acc, b| acc << 8 | *b as u64),
+            26 => self.take(4)?.iter().fold(0, |acc, b| acc << 8 | *b as u64),
+            27 => self.take(8)?.iter().fold(0, |acc, b| acc << 8 | *b as u64),
+            // Reserved values and indefinite lengths.
+            _ => return None,
+        };
+        Some((major, argument))
+    }
+
+    fn int(&mut self) -> Option<i64> {
+        match self.head()? {
+            (MAJOR_UINT, n) if n <= i64::MAX as u64 => Some(n as i64),
+            (MAJOR_NINT, n) if n <= i64::MAX as u64 => Some(-1 - n as i64),
+            _ => None,
+        }
+    }
+
+    fn coordinate(&mut self) -> Option<[u8; 32]> {
+        match self.head()? {
+            (MAJOR_BSTR, 32) => {
+                let mut coordinate = [0u8; 32];
+                coordinate.copy_from_slice(self.take(32)?);
+                Some(coordinate)
+            }
+            _ => None,
+        }
+    }
+
+    /// Skip over the next data item, including any nested items.
+    fn skip(&mut self, depth: usize) -> Option<()> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+
+        match self.head()? {
+            (MAJOR_UINT, _) | (MAJOR_NINT, _) => {}
+            (MAJOR_BSTR, len) | (MAJOR_TSTR, len) => {
+                self.take(usize::try_from(len).ok()?)?;
+            }
+            (MAJOR_ARRAY, len) => {
+                for _ in 0..len {
+                    self.skip(depth + 1)?;
+                }
+            }
+            (MAJOR_MAP, len) => {
+                for _ in 0..len {
+                    self.skip(depth + 1)?;
+                    self.skip(depth + 1)?;
+                }
+            }
+            (MAJOR_TAG, _) => self.skip(depth + 1)?,
+            // Simple values; floats are not used in COSE_Key parameters.
+            (_, 20..=22) => {}
+            _ => return None,
+        }
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::StaticSecret;
+
+    use rand_core::OsRng;
+
+    #[test]
+    fn cose_key_roundtrip() {
+        let secret = StaticSecret::new(OsRng);
+        let public = PublicKey::from(&secret);
+
+        let encoded = public.to_cose_key();
+        assert_eq!(PublicKey::from_cose_key(&encoded), Some(public));
+
+        // Trailing bytes and truncation are rejected.
+        let mut long = [0u8; COSE_KEY_LEN + 1];
+        long[..COSE_KEY_LEN].copy_from_slice(&encoded);
+        assert_eq!(PublicKey::from_cose_key(&long), None);
+        assert_eq!(PublicKey::from_cose_key(&encoded[..COSE_KEY_LEN - 1]), None);
+
+        // A point off the curve is rejected.
+        let mut off_curve = encoded;
+        off_curve[COSE_KEY_LEN - 1] ^= 1;
+        assert_eq!(PublicKey::from_cose_key(&off_curve), None);
+
+        // A different curve is rejected.
+        let mut other_curve = encoded;
+        other_curve[8] = 0x01;
+        assert_eq!(PublicKey::from_cose_key(&other_curve), None);
+    }
+
+    #[test]
+    fn cose_key_ignores_unknown_parameters() {
+        let public = PublicKey::from(&StaticSecret::new(OsRng));
+        let encoded = public.to_cose_key();
+
+        // { 2: h'01', <x, y, kty, crv>, 4: [1, "x"] } with the parameters reordered
+        let mut reordered = [0u8; COSE_KEY_LEN + 8];
+        reordered[0] = 0xa6;
+        reordered[1..4].copy_from_slice(&[0x02, 0x41, 0x01]);
+        reordered[4..74].copy_from_slice(&encoded[9..]);
+        reordered[74..82].copy_from_slice(&encoded[1..9]);
+        reordered[82..].copy_from_slice(&[0x04, 0x82, 0x01, 0x61, 0x78]);
+        assert_eq!(PublicKey::from_cose_key(&reordered), Some(public));
+    }
+}
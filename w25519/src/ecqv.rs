@@ -0,0 +1,262 @@
+//! Elliptic Curve Qu-Vanstone implicit certificates ([SEC 4])
+//!
+//! An implicit certificate binds a public key to the certificate information (subject,
+//! validity, ...) without carrying a signature: anyone holding the certificate and the CA's
+//! [`PublicKey`] reconstructs the subject's public key with [`ImplicitCertificate::public_key`],
+//! and only the subject can derive the matching private key.
+//!
+//! 1. The requester draws a [`RequestSecret`] and sends its [`PublicKey`] to the CA.
+//! 2. The CA calls [`issue`] with the certificate information and returns the
+//!    [`ImplicitCertificate`] and the [`PrivateKeyReconstruction`] data.
+//! 3. The requester calls [`RequestSecret::derive_private_key`], which yields a
+//!    [`DerivedSecret`] usable for Diffie-Hellman like a [`StaticSecret`].
+//!
+//! The encoding of the certificate information is left to the application; it is hashed
+//! together with the reconstruction point, so it has to be transmitted alongside the
+//! certificate byte for byte.
+//!
+//! All arithmetic takes place in the prime-order subgroup: reconstruction points with a
+//! small-order component are rejected, and derived secrets clear the cofactor of peer keys
+//! like clamped secrets, so the usual Diffie-Hellman behaviour for small-order peer keys is
+//! kept.
+//!
+//! [SEC 4]: https://www.secg.org/sec4-1.0.pdf
+
+use curve25519_dalek::constants::{BASEPOINT_ORDER, WEI25519_BASEPOINT};
+use curve25519_dalek::digest::Digest;
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::weierstrass::WeierstrassPoint;
+
+use rand_core::CryptoRng;
+use rand_core::RngCore;
+
+use sha2::Sha512;
+
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::w25519::{DerivedSecret, PublicKey, StaticSecret};
+
+/// The requester's secret for a pending certificate request.
+#[derive(Zeroize)]
+#[zeroize(drop)]
+pub struct RequestSecret(Scalar);
+
+impl RequestSecret {
+    /// Generate a new request secret.
+    pub fn new<T: RngCore + CryptoRng>(mut csprng: T) -> Self {
+        RequestSecret(Scalar::random(&mut csprng))
+    }
+
+    /// Derive the private key certified by `certificate`, using the CA's `reconstruction`
+    /// data.
+    ///
+    /// The derived key is generally not a clamped scalar, so it is a [`DerivedSecret`], which
+    /// is stored with [`DerivedSecret::to_bytes`] and loaded with [`DerivedSecret::from_bytes`].
+    ///
+    /// Returns `None` if the resulting private key does not match the public key
+    /// reconstructed from `certificate`, `cert_info` and `ca_public`.
+    pub fn derive_private_key(
+        &self,
+        certificate: &ImplicitCertificate,
+        cert_info: &[u8],
+        reconstruction: &PrivateKeyReconstruction,
+        ca_public: &PublicKey,
+    ) -> Option<DerivedSecret> {
+        let e = certificate.hash(cert_info);
+        let secret = DerivedSecret(e * self.0 + reconstruction.0);
+
+        if PublicKey::from(&secret) == certificate.public_key(cert_info, ca_public) {
+            Some(secret)
+        } else {
+            None
+        }
+    }
+
+    /// Extract this request secret's bytes for storage.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_bytes()
+    }
+
+    /// Extract this request secret's bytes for storage, wiping them when they are dropped.
+    pub fn to_bytes_zeroizing(&self) -> Zeroizing<[u8; 32]> {
+        Zeroizing::new(self.to_bytes())
+    }
+}
+
+impl From<[u8; 32]> for RequestSecret {
+    /// Load a request secret from a byte array.
+    fn from(bytes: [u8; 32]) -> RequestSecret {
+        RequestSecret(Scalar::from_bytes_mod_order(bytes))
+    }
+}
+
+impl<'a> From<&'a RequestSecret> for PublicKey {
+    /// Given a [`RequestSecret`], compute the [`PublicKey`] sent to the CA.
+    fn from(secret: &'a RequestSecret) -> PublicKey {
+        PublicKey(WEI25519_BASEPOINT * secret.0)
+    }
+}
+
+/// An implicit certificate, i.e. the public key reconstruction point.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct ImplicitCertificate(WeierstrassPoint);
+
+impl ImplicitCertificate {
+    /// Reconstruct the subject's public key from this certificate and the CA's public key.
+    pub fn public_key(&self, cert_info: &[u8], ca_public: &PublicKey) -> PublicKey {
+        PublicKey(self.hash(cert_info) * self.0 + ca_public.0)
+    }
+
+    /// Convert this certificate to bytes, see [`PublicKey::compress`].
+    pub fn to_bytes(&self) -> [u8; 33] {
+        PublicKey(self.0).compress()
+    }
+
+    /// Load a certificate from bytes.
+    ///
+    /// Returns `None` if the bytes are not a valid point in the prime-order subgroup.
+    pub fn from_bytes(bytes: &[u8; 33]) -> Option<ImplicitCertificate> {
+        let point = PublicKey::decompress(bytes)?.0;
+
+        if is_torsion_free(&point) {
+            Some(ImplicitCertificate(point))
+        } else {
+            None
+        }
+    }
+
+    fn hash(&self, cert_info: &[u8]) -> Scalar {
+        Scalar::from_hash(
+            Sha512::new()
+                .chain(b"w25519 ECQV")
+                .chain(&self.to_bytes()[..])
+                .chain(cert_info),
+        )
+    }
+}
+
+/// The private key reconstruction data returned by the CA alongside an
+/// [`ImplicitCertificate`].
+#[derive(Clone)]
+pub struct PrivateKeyReconstruction(Scalar);
+
+impl PrivateKeyReconstruction {
+    /// Convert the reconstruction data to bytes.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_bytes()
+    }
+
+    /// Load reconstruction data from bytes.
+    ///
+    /// Returns `None` if the bytes are not a canonical scalar.
+    pub fn from_bytes(bytes: [u8; 32]) -> Option<PrivateKeyReconstruction> {
+        Scalar::from_canonical_bytes(bytes).map(PrivateKeyReconstruction)
+    }
+}
+
+/// Issue an implicit certificate for `request`, binding it to `cert_info`.
+///
+/// Returns `None` if `request` is not a point on Wei25519 in the prime-order subgroup other
+/// than the point at infinity.
+pub fn issue<T: RngCore + CryptoRng>(
+    ca_secret: &StaticSecret,
+    request: &PublicKey,
+    cert_info: &[u8],
+    mut csprng: T,
+) -> Option<(ImplicitCertificate, PrivateKeyReconstruction)> {
+    if !request.is_valid() || !is_torsion_free(&request.0) {
+        return None;
+    }
+
+    let mut k = Scalar::random(&mut csprng);
+    let certificate = ImplicitCertificate(request.0 + WEI25519_BASEPOINT * k);
+
+    let r = certificate.hash(cert_info) * k + ca_secret.0.reduce();
+    k.zeroize();
+
+    Some((certificate, PrivateKeyReconstruction(r)))
+}
+
+fn is_torsion_free(point: &WeierstrassPoint) -> bool {
+    *point * BASEPOINT_ORDER == WeierstrassPoint::default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rand_core::OsRng;
+
+    #[test]
+    fn ecqv_issue_and_reconstruct() {
+        let ca_secret = StaticSecret::new(OsRng);
+        let ca_public = PublicKey::from(&ca_secret);
+        let cert_info = b"subject=meter-0042;not_after=2030-01-01";
+
+        let request_secret = RequestSecret::new(OsRng);
+        let request = PublicKey::from(&request_secret);
+
+        let (certificate, reconstruction) = issue(&ca_secret, &request, cert_info, OsRng).unwrap();
+        let certificate = ImplicitCertificate::from_bytes(&certificate.to_bytes()).unwrap();
+        let reconstruction = PrivateKeyReconstruction::from_bytes(reconstruction.to_bytes()).unwrap();
+
+        let public = certificate.public_key(cert_info, &ca_public);
+        let request_secret = RequestSecret::from(request_secret.to_bytes());
+        let secret = request_secret
+            .derive_private_key(&certificate, cert_info, &reconstruction, &ca_public)
+            .unwrap();
+        assert_eq!(PublicKey::from(&secret), public);
+
+        let peer = StaticSecret::new(OsRng);
+        assert_eq!(
+            secret.diffie_hellman(&PublicKey::from(&peer)).to_bytes(),
+            peer.diffie_hellman(&public).to_bytes()
+        );
+
+        // The certificate does not verify under different certificate information.
+        let request_secret = RequestSecret::new(OsRng);
+        let (certificate, reconstruction) =
+            issue(&ca_secret, &PublicKey::from(&request_secret), cert_info, OsRng).unwrap();
+        assert!(request_secret
+            .derive_private_key(&certificate, b"subject=other", &reconstruction, &ca_public)
+            .is_none());
+    }
+
+    #[test]
+    fn derived_private_key_round_trip() {
+        let ca_secret = StaticSecret::new(OsRng);
+        let ca_public = PublicKey::from(&ca_secret);
+        let cert_info = b"subject=meter-0042";
+
+        let request_secret = RequestSecret::new(OsRng);
+        let (certificate, reconstruction) =
+            issue(&ca_secret, &PublicKey::from(&request_secret), cert_info, OsRng).unwrap();
+        let secret = request_secret
+            .derive_private_key(&certificate, cert_info, &reconstruction, &ca_public)
+            .unwrap();
+
+        let restored = DerivedSecret::from_bytes(secret.to_bytes()).unwrap();
+        assert_eq!(PublicKey::from(&restored), certificate.public_key(cert_info, &ca_public));
+
+        let peer = PublicKey::from(&StaticSecret::new(OsRng));
+        assert_eq!(
+            restored.diffie_hellman(&peer).to_bytes(),
+            secret.diffie_hellman(&peer).to_bytes()
+        );
+
+        assert!(DerivedSecret::from_bytes([0u8; 32]).is_none());
+        assert!(DerivedSecret::from_bytes([0xff; 32]).is_none());
+    }
+
+    #[test]
+    fn issue_rejects_invalid_requests() {
+        let ca_secret = StaticSecret::new(OsRng);
+        let request = PublicKey::from(&RequestSecret::new(OsRng));
+
+        let mut off_curve = request.to_bytes();
+        off_curve[32] ^= 1;
+        assert!(issue(&ca_secret, &PublicKey::from(off_curve), b"", OsRng).is_none());
+        assert!(issue(&ca_secret, &PublicKey::from([0u8; 64]), b"", OsRng).is_none());
+        assert!(issue(&ca_secret, &request, b"", OsRng).is_some());
+    }
+}
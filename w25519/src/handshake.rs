@@ -0,0 +1,311 @@
+//! An ephemeral-ephemeral Diffie-Hellman handshake
+//!
+//! [`Handshake`] runs the usual sequence in a fixed order:
+//!
+//! 1. generate an [`EphemeralSecret`] and encode its [`PublicKey`] as a [`KeyShare`] in the
+//!    selected [`WireFormat`],
+//! 2. decode and validate the counterparty's key share, rejecting invalid and small-order
+//!    keys,
+//! 3. perform the Diffie-Hellman key agreement,
+//! 4. derive the [`SessionKey`] with HKDF-SHA-256, using the hash of the transcript (both key
+//!    shares and an optional context) as salt,
+//! 5. optionally exchange key confirmation tags before releasing the session key.
+//!
+//! ```text
+//! transcript = SHA-256("w25519 handshake" || format || len(context) || context
+//!                      || initiator key share || responder key share)
+//! session key || confirmation key = HKDF-SHA-256(salt = transcript, IKM = Z,
+//!                                                info = "w25519 handshake", L = 64)
+//! tag = HMAC-SHA-256(confirmation key, "initiator" | "responder")
+//! ```
+//!
+//! where `len` is the 4-byte big-endian length of the context.  The handshake does not
+//! authenticate the counterparty; bind long-term identities into the context or confirm the
+//! transcript hash over an authenticated channel.
+
+use core::convert::TryFrom;
+
+use curve25519_dalek::digest::Digest;
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac, NewMac};
+use sha2::Sha256;
+
+use rand_core::CryptoRng;
+use rand_core::RngCore;
+
+use zeroize::Zeroize;
+
+use crate::shared_secret::Role;
+use crate::w25519::{EphemeralSecret, PublicKey};
+
+/// The `info` used to derive the session and confirmation keys.
+const HANDSHAKE_INFO: &[u8] = b"w25519 handshake";
+
+/// The encoding of the key shares exchanged in a [`Handshake`].
+///
+/// Both parties have to use the same wire format.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum WireFormat {
+    /// The 64-byte encoding of [`PublicKey::to_bytes`].
+    Raw,
+    /// The 33-byte SEC1 compressed encoding of [`PublicKey::compress`].
+    Compressed,
+}
+
+impl WireFormat {
+    fn len(self) -> usize {
+        match self {
+            WireFormat::Raw => 64,
+            WireFormat::Compressed => 33,
+        }
+    }
+
+    fn encode(self, public: &PublicKey) -> KeyShare {
+        let mut bytes = [0u8; 64];
+        match self {
+            WireFormat::Raw => bytes.copy_from_slice(&public.0.canonical().to_bytes()),
+            WireFormat::Compressed => bytes[..33].copy_from_slice(&public.compress()),
+        }
+
+        KeyShare { bytes, len: self.len() }
+    }
+
+    /// Decode and validate a key share, accepting only canonical encodings of points on
+    /// Wei25519 other than the point at infinity.
+    fn decode(self, bytes: &[u8]) -> Option<PublicKey> {
+        if bytes.len() != self.len() {
+            return None;
+        }
+
+        match self {
+            WireFormat::Raw => {
+                let mut x = [0u8; 32];
+                let mut y = [0u8; 32];
+                x.copy_from_slice(&bytes[..32]);
+                y.copy_from_slice(&bytes[32..]);
+                PublicKey::from_coordinates(x, y)
+            }
+            WireFormat::Compressed => PublicKey::decompress(<&[u8; 33]>::try_from(bytes).ok()?),
+        }
+    }
+}
+
+/// A public key encoded in a [`WireFormat`], to be sent to the counterparty.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct KeyShare {
+    bytes: [u8; 64],
+    len: usize,
+}
+
+impl KeyShare {
+    /// View this key share as bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+impl AsRef<[u8]> for KeyShare {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+/// One party's state of an ephemeral-ephemeral Diffie-Hellman handshake.
+pub struct Handshake<'a> {
+    role: Role,
+    format: WireFormat,
+    context: &'a [u8],
+    secret: EphemeralSecret,
+}
+
+impl<'a> Handshake<'a> {
+    /// Start a handshake as `role`, generating a fresh ephemeral secret with `csprng`.
+    ///
+    /// The wire format defaults to [`WireFormat::Compressed`] and the context to empty.
+    pub fn new<T: RngCore + CryptoRng>(role: Role, csprng: T) -> Self {
+        Handshake {
+            role,
+            format: WireFormat::Compressed,
+            context: &[],
+            secret: EphemeralSecret::new(csprng),
+        }
+    }
+
+    /// Select the wire format of the key shares.
+    pub fn with_wire_format(mut self, format: WireFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Bind the handshake to `context`, e.g. a protocol name and the parties' identities,
+    /// which has to be the same for both parties.
+    pub fn with_context(mut self, context: &'a [u8]) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// The key share to send to the counterparty.
+    pub fn key_share(&self) -> KeyShare {
+        self.format.encode(&PublicKey::from(&self.secret))
+    }
+
+    /// Complete the handshake with the counterparty's key share, without key confirmation.
+    ///
+    /// Returns `None` if the key share is invalid or the key agreement is not contributory.
+    pub fn finish(self, their_share: &[u8]) -> Option<SessionKey> {
+        self.finish_with_confirmation(their_share)
+            .map(|unconfirmed| SessionKey(unconfirmed.session_key))
+    }
+
+    /// Complete the handshake with the counterparty's key share, holding back the session key
+    /// until the counterparty's key confirmation tag is verified.
+    ///
+    /// Returns `None` if the key share is invalid or the key agreement is not contributory.
+    pub fn finish_with_confirmation(self, their_share: &[u8]) -> Option<Unconfirmed> {
+        let their_public = self.format.decode(their_share)?;
+        let our_share = self.key_share();
+
+        let (initiator_share, responder_share) = match self.role {
+            Role::Initiator => (our_share.as_bytes(), their_share),
+            Role::Responder => (their_share, our_share.as_bytes()),
+        };
+        let transcript = Sha256::new()
+            .chain(HANDSHAKE_INFO)
+            .chain([self.format as u8])
+            .chain((self.context.len() as u32).to_be_bytes())
+            .chain(self.context)
+            .chain(initiator_share)
+            .chain(responder_share)
+            .finalize();
+
+        let shared = self.secret.diffie_hellman_checked(&their_public)?;
+        if !shared.was_contributory() {
+            return None;
+        }
+
+        let mut okm = [0u8; 64];
+        Hkdf::<Sha256>::new(Some(&transcript), shared.as_bytes())
+            .expand(HANDSHAKE_INFO, &mut okm)
+            .expect("64 bytes is a valid length for HKDF-SHA-256");
+
+        let mut unconfirmed = Unconfirmed {
+            role: self.role,
+            session_key: [0u8; 32],
+            confirmation_key: [0u8; 32],
+        };
+        unconfirmed.session_key.copy_from_slice(&okm[..32]);
+        unconfirmed.confirmation_key.copy_from_slice(&okm[32..]);
+        okm.zeroize();
+
+        Some(unconfirmed)
+    }
+}
+
+/// A completed handshake whose session key is released only after key confirmation.
+#[derive(Zeroize)]
+#[zeroize(drop)]
+pub struct Unconfirmed {
+    #[zeroize(skip)]
+    role: Role,
+    session_key: [u8; 32],
+    confirmation_key: [u8; 32],
+}
+
+impl Unconfirmed {
+    /// The key confirmation tag to send to the counterparty.
+    pub fn confirmation_tag(&self) -> [u8; 32] {
+        self.mac(self.role).finalize().into_bytes().into()
+    }
+
+    /// Verify in constant time the counterparty's key confirmation tag.
+    ///
+    /// Returns the [`SessionKey`] if the counterparty derived the same keys, or `None`
+    /// otherwise.
+    pub fn confirm(self, their_tag: &[u8; 32]) -> Option<SessionKey> {
+        let their_role = match self.role {
+            Role::Initiator => Role::Responder,
+            Role::Responder => Role::Initiator,
+        };
+
+        if self.mac(their_role).verify(their_tag).is_ok() {
+            Some(SessionKey(self.session_key))
+        } else {
+            None
+        }
+    }
+
+    fn mac(&self, role: Role) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.confirmation_key)
+            .expect("HMAC can take a key of any size");
+        mac.update(match role {
+            Role::Initiator => b"initiator",
+            Role::Responder => b"responder",
+        });
+        mac
+    }
+}
+
+/// The key resulting from a [`Handshake`].
+#[derive(Zeroize)]
+#[zeroize(drop)]
+pub struct SessionKey([u8; 32]);
+
+impl SessionKey {
+    /// View this session key as a byte array.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rand_core::OsRng;
+
+    #[test]
+    fn handshake_with_confirmation() {
+        for format in [WireFormat::Raw, WireFormat::Compressed].iter() {
+            let initiator = Handshake::new(Role::Initiator, OsRng)
+                .with_wire_format(*format)
+                .with_context(b"test protocol");
+            let responder = Handshake::new(Role::Responder, OsRng)
+                .with_wire_format(*format)
+                .with_context(b"test protocol");
+
+            let initiator_share = initiator.key_share();
+            let responder_share = responder.key_share();
+            assert_eq!(initiator_share.as_bytes().len(), format.len());
+
+            let initiator = initiator.finish_with_confirmation(responder_share.as_bytes()).unwrap();
+            let responder = responder.finish_with_confirmation(initiator_share.as_bytes()).unwrap();
+
+            let initiator_tag = initiator.confirmation_tag();
+            let responder_tag = responder.confirmation_tag();
+            assert_ne!(initiator_tag, responder_tag);
+
+            let initiator_key = initiator.confirm(&responder_tag).unwrap();
+            let responder_key = responder.confirm(&initiator_tag).unwrap();
+            assert_eq!(initiator_key.as_bytes(), responder_key.as_bytes());
+        }
+    }
+
+    #[test]
+    fn handshake_binds_transcript() {
+        let initiator = Handshake::new(Role::Initiator, OsRng).with_context(b"a");
+        let responder = Handshake::new(Role::Responder, OsRng).with_context(b"b");
+        let initiator_share = initiator.key_share();
+        let responder_share = responder.key_share();
+
+        let initiator = initiator.finish_with_confirmation(responder_share.as_bytes()).unwrap();
+        let responder = responder.finish_with_confirmation(initiator_share.as_bytes()).unwrap();
+        assert!(initiator.confirm(&responder.confirmation_tag()).is_none());
+
+        // Invalid key shares are rejected.
+        assert!(Handshake::new(Role::Initiator, OsRng).finish(&[0u8; 33]).is_none());
+        assert!(Handshake::new(Role::Initiator, OsRng)
+            .with_wire_format(WireFormat::Raw)
+            .finish(&[0u8; 64])
+            .is_none());
+    }
+}
@@ -0,0 +1,191 @@
+//! Verifiable key generation with a seed commitment
+//!
+//! [`generate`] derives a [`StaticSecret`] from a fresh random seed with
+//! [`StaticSecret::from_seed`] and outputs, alongside the key, a [`KeygenRecord`] holding
+//!
+//! ```text
+//! C = SHA-256("w25519 seed commitment" || len(domain) || domain || seed)
+//! P = s·G
+//! (R, z) = Schnorr proof of knowledge of s, with
+//!          k random, R = k·G, z = k + c·s mod l and
+//!          c = SHA-512("w25519 keygen proof" || C || P || R) mod l
+//! ```
+//!
+//! where `len` is the 4-byte big-endian length and `P` and `R` are SEC1 compressed.
+//!
+//! [`KeygenRecord::verify`] checks the proof, which shows that whoever generated the
+//! record held the secret key of `P` and bound it to `C` at generation time, so the key was
+//! not imported under a commitment made up later.  The proof can not show that `s` was
+//! derived from the committed seed, which would need a zero-knowledge proof of the hash
+//! computation; an auditor who is given the seed checks the derivation with
+//! [`KeygenRecord::verify_opening`].
+
+use curve25519_dalek::constants::WEI25519_BASEPOINT;
+use curve25519_dalek::digest::Digest;
+use curve25519_dalek::scalar::Scalar;
+
+use rand_core::CryptoRng;
+use rand_core::RngCore;
+
+use sha2::{Sha256, Sha512};
+
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::w25519::{PublicKey, StaticSecret};
+
+/// The length of the encoding of a [`KeygenRecord`].
+pub const KEYGEN_RECORD_LEN: usize = 130;
+
+/// The public evidence that a key was generated by [`generate`].
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct KeygenRecord {
+    public_key: PublicKey,
+    commitment: [u8; 32],
+    proof_r: PublicKey,
+    proof_z: Scalar,
+}
+
+impl KeygenRecord {
+    /// The generated public key.
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    /// The commitment to the derivation seed.
+    pub fn commitment(&self) -> &[u8; 32] {
+        &self.commitment
+    }
+
+    /// Verify the proof that the generator of this record knew the secret key and bound it to
+    /// the seed commitment.
+    #[must_use]
+    pub fn verify(&self) -> bool {
+        let c = challenge(&self.commitment, &self.public_key, &self.proof_r);
+
+        WEI25519_BASEPOINT * self.proof_z == self.proof_r.0 + self.public_key.0 * c
+    }
+
+    /// Verify the opening of the seed commitment, i.e. that `seed` matches the commitment and
+    /// derives the public key in `domain`.
+    #[must_use]
+    pub fn verify_opening(&self, seed: &[u8; 32], domain: &[u8]) -> bool {
+        commitment(seed, domain) == self.commitment
+            && PublicKey::from(&StaticSecret::from_seed(seed, domain)) == self.public_key
+    }
+
+    /// Convert this record to bytes: the compressed public key, the commitment, and the
+    /// compressed `R` and the scalar `z` of the proof.
+    pub fn to_bytes(&self) -> [u8; KEYGEN_RECORD_LEN] {
+        let mut bytes = [0u8; KEYGEN_RECORD_LEN];
+        bytes[..33].copy_from_slice(&self.public_key.compress());
+        bytes[33..65].copy_from_slice(&self.commitment);
+        bytes[65..98].copy_from_slice(&self.proof_r.compress());
+        bytes[98..].copy_from_slice(self.proof_z.as_bytes());
+        bytes
+    }
+
+    /// Load a record from bytes, see [`KeygenRecord::to_bytes`].
+    ///
+    /// Returns `None` if the points or the scalar are not canonical.  The proof is not
+    /// verified.
+    pub fn from_bytes(bytes: &[u8; KEYGEN_RECORD_LEN]) -> Option<KeygenRecord> {
+        let mut public_key = [0u8; 33];
+        let mut commitment = [0u8; 32];
+        let mut proof_r = [0u8; 33];
+        let mut proof_z = [0u8; 32];
+        public_key.copy_from_slice(&bytes[..33]);
+        commitment.copy_from_slice(&bytes[33..65]);
+        proof_r.copy_from_slice(&bytes[65..98]);
+        proof_z.copy_from_slice(&bytes[98..]);
+
+        Some(KeygenRecord {
+            public_key: PublicKey::decompress(&public_key)?,
+            commitment,
+            proof_r: PublicKey::decompress(&proof_r)?,
+            proof_z: Scalar::from_canonical_bytes(proof_z)?,
+        })
+    }
+}
+
+/// Generate a [`StaticSecret`] from a fresh seed derived with `domain`, see the
+/// [module documentation](crate::keygen).
+///
+/// Returns the secret key, the seed to open the commitment to an auditor, and the
+/// [`KeygenRecord`] to publish.  Discard the seed if the derivation is never to be audited.
+pub fn generate<T: RngCore + CryptoRng>(
+    domain: &[u8],
+    mut csprng: T,
+) -> (StaticSecret, Zeroizing<[u8; 32]>, KeygenRecord) {
+    let mut seed = Zeroizing::new([0u8; 32]);
+    csprng.fill_bytes(&mut seed[..]);
+
+    let secret = StaticSecret::from_seed(&seed[..], domain);
+    let public_key = PublicKey::from(&secret);
+    let commitment = commitment(&seed, domain);
+
+    let mut k = Scalar::random(&mut csprng);
+    let proof_r = PublicKey(WEI25519_BASEPOINT * k);
+    let c = challenge(&commitment, &public_key, &proof_r);
+
+    let mut s = secret.0.reduce();
+    let proof_z = k + c * s;
+    k.zeroize();
+    s.zeroize();
+
+    let record = KeygenRecord {
+        public_key,
+        commitment,
+        proof_r,
+        proof_z,
+    };
+    (secret, seed, record)
+}
+
+fn commitment(seed: &[u8; 32], domain: &[u8]) -> [u8; 32] {
+    Sha256::new()
+        .chain(b"w25519 seed commitment")
+        .chain((domain.len() as u32).to_be_bytes())
+        .chain(domain)
+        .chain(seed)
+        .finalize()
+        .into()
+}
+
+fn challenge(commitment: &[u8; 32], public_key: &PublicKey, proof_r: &PublicKey) -> Scalar {
+    Scalar::from_hash(
+        Sha512::new()
+            .chain(b"w25519 keygen proof")
+            .chain(commitment)
+            .chain(&public_key.compress()[..])
+            .chain(&proof_r.compress()[..]),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rand_core::OsRng;
+
+    #[test]
+    fn verifiable_keygen() {
+        let (secret, seed, record) = generate(b"meter-0042", OsRng);
+        let record = KeygenRecord::from_bytes(&record.to_bytes()).unwrap();
+
+        assert_eq!(*record.public_key(), PublicKey::from(&secret));
+        assert!(record.verify());
+        assert!(record.verify_opening(&seed, b"meter-0042"));
+        assert!(!record.verify_opening(&seed, b"meter-0043"));
+        assert!(!record.verify_opening(&[0u8; 32], b"meter-0042"));
+
+        // The proof does not verify for another key or commitment.
+        let (_, _, other) = generate(b"meter-0042", OsRng);
+        let mut forged = record;
+        forged.public_key = other.public_key;
+        assert!(!forged.verify());
+
+        let mut forged = record;
+        forged.commitment = other.commitment;
+        assert!(!forged.verify());
+    }
+}
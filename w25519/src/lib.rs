@@ -2,6 +2,34 @@
 
 extern crate curve25519_dalek;
 
+mod shared_secret;
 mod w25519;
 
-pub use crate::w25519::*;
\ No newline at end of file
+pub mod agreement;
+
+#[cfg(feature = "cose")]
+pub mod cose;
+
+pub mod ecqv;
+pub mod handshake;
+pub mod keygen;
+
+#[cfg(feature = "reusable_secrets")]
+pub mod ratchet;
+
+#[cfg(any(feature = "rand_core_06", feature = "rand_core_09", feature = "embedded_hal"))]
+pub mod rng;
+
+pub mod rotation;
+
+pub mod threshold;
+
+pub use crate::shared_secret::*;
+pub use crate::w25519::*;
+
+/// Scalars modulo the group order, e.g. for deriving nonces, challenges or keys uniformly via
+/// [`Scalar::from_bytes_mod_order_wide`] or [`Scalar::from_hash`].
+pub use curve25519_dalek::scalar::Scalar;
+
+/// The `digest` crate used by [`Scalar::from_hash`] and [`Scalar::hash_from_bytes`].
+pub use curve25519_dalek::digest;
@@ -0,0 +1,252 @@
+//! The Diffie-Hellman ratchet step of the Double Ratchet algorithm
+//!
+//! This implements the DH ratchet as specified in
+//! [The Double Ratchet Algorithm](https://signal.org/docs/specifications/doubleratchet/),
+//! using [`ReusableSecret`]s as ratchet keys and HKDF-SHA-256 for `KDF_RK` and HMAC-SHA-256
+//! for `KDF_CK`.
+//!
+//! Header encryption, message numbering and the handling of skipped message keys are left to
+//! the caller.
+//!
+//! Ratchet public keys are received from the counterparty, so they are validated and a
+//! non-contributory Diffie-Hellman output is rejected, which keeps a hostile peer from pinning
+//! the root key to a value it knows.
+
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac, NewMac};
+use sha2::Sha256;
+use x25519_dalek::SharedSecret;
+
+use rand_core::CryptoRng;
+use rand_core::RngCore;
+
+use zeroize::Zeroize;
+
+use crate::w25519::{PublicKey, ReusableSecret};
+
+/// The `info` used for `KDF_RK`.
+const ROOT_KDF_INFO: &[u8] = b"w25519 double ratchet";
+
+/// The DH ratchet state of one party: the root key, the own ratchet key pair and the
+/// counterparty's current ratchet public key.
+pub struct DhRatchet {
+    root_key: [u8; 32],
+    our_secret: ReusableSecret,
+    their_public: Option<PublicKey>,
+}
+
+impl DhRatchet {
+    /// Initialize the ratchet of the party sending the first message, given the shared
+    /// secret key agreed on beforehand (e.g. with X3DH) and the counterparty's ratchet
+    /// public key.
+    ///
+    /// Returns the ratchet and the initial sending [`ChainKey`], or `None` if `their_public`
+    /// is invalid or the Diffie-Hellman output is not contributory.
+    pub fn new_initiator<T: RngCore + CryptoRng>(
+        shared_key: [u8; 32],
+        their_public: &PublicKey,
+        csprng: T,
+    ) -> Option<(DhRatchet, ChainKey)> {
+        let our_secret = ReusableSecret::new(csprng);
+        let dh_out = diffie_hellman(&our_secret, their_public)?;
+
+        let mut ratchet = DhRatchet {
+            root_key: shared_key,
+            our_secret,
+            their_public: Some(*their_public),
+        };
+        let sending = ratchet.kdf_rk(&dh_out);
+
+        Some((ratchet, sending))
+    }
+
+    /// Initialize the ratchet of the party receiving the first message, given the shared
+    /// secret key agreed on beforehand and the own ratchet secret key whose public key is
+    /// known to the initiator.
+    pub fn new_responder(shared_key: [u8; 32], our_secret: ReusableSecret) -> DhRatchet {
+        DhRatchet {
+            root_key: shared_key,
+            our_secret,
+            their_public: None,
+        }
+    }
+
+    /// The current own ratchet public key, which is sent along with each message.
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey::from(&self.our_secret)
+    }
+
+    /// The most recently received ratchet public key of the counterparty.
+    pub fn their_public_key(&self) -> Option<PublicKey> {
+        self.their_public
+    }
+
+    /// Perform a DH ratchet step upon receiving a new ratchet public key from the
+    /// counterparty: derive the receiving chain, generate a new own ratchet key and derive
+    /// the sending chain.
+    ///
+    /// Returns the new receiving and sending [`ChainKey`]s, or `None` if `their_public` is
+    /// invalid or a Diffie-Hellman output is not contributory, in which case the ratchet is
+    /// left unchanged.
+    pub fn ratchet<T: RngCore + CryptoRng>(
+        &mut self,
+        their_public: &PublicKey,
+        csprng: T,
+    ) -> Option<(ChainKey, ChainKey)> {
+        let receiving_dh = diffie_hellman(&self.our_secret, their_public)?;
+        let our_secret = ReusableSecret::new(csprng);
+        let sending_dh = diffie_hellman(&our_secret, their_public)?;
+
+        self.their_public = Some(*their_public);
+        let receiving = self.kdf_rk(&receiving_dh);
+
+        self.our_secret = our_secret;
+        let sending = self.kdf_rk(&sending_dh);
+
+        Some((receiving, sending))
+    }
+
+    /// `KDF_RK(rk, dh_out)`, updating the root key in place.
+    fn kdf_rk(&mut self, dh_out: &SharedSecret) -> ChainKey {
+        let mut okm = [0u8; 64];
+        Hkdf::<Sha256>::new(Some(&self.root_key), dh_out.as_bytes())
+            .expand(ROOT_KDF_INFO, &mut okm)
+            .expect("64 bytes is a valid HKDF-SHA-256 output length");
+
+        let mut chain_key = [0u8; 32];
+        self.root_key.copy_from_slice(&okm[..32]);
+        chain_key.copy_from_slice(&okm[32..]);
+        okm.zeroize();
+
+        ChainKey(chain_key)
+    }
+}
+
+/// `DH(our_secret, their_public)`, rejecting invalid public keys and non-contributory outputs.
+fn diffie_hellman(our_secret: &ReusableSecret, their_public: &PublicKey) -> Option<SharedSecret> {
+    if !their_public.is_valid() {
+        return None;
+    }
+
+    our_secret
+        .diffie_hellman_checked(their_public)
+        .filter(SharedSecret::was_contributory)
+}
+
+impl Drop for DhRatchet {
+    fn drop(&mut self) {
+        self.root_key.zeroize();
+    }
+}
+
+/// A sending or receiving chain key of the symmetric-key ratchet.
+#[derive(Zeroize)]
+#[zeroize(drop)]
+pub struct ChainKey([u8; 32]);
+
+impl ChainKey {
+    /// Advance the chain, `KDF_CK(ck)`, and return the next [`MessageKey`].
+    pub fn next_message_key(&mut self) -> MessageKey {
+        let message_key = self.hmac(0x01);
+        let chain_key = self.hmac(0x02);
+        self.0 = chain_key;
+
+        MessageKey(message_key)
+    }
+
+    /// View this chain key as a byte array.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    fn hmac(&self, constant: u8) -> [u8; 32] {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.0)
+            .expect("HMAC accepts keys of any length");
+        mac.update(&[constant]);
+        mac.finalize().into_bytes().into()
+    }
+}
+
+/// A key for encrypting a single message, derived from a [`ChainKey`].
+#[derive(Zeroize)]
+#[zeroize(drop)]
+pub struct MessageKey([u8; 32]);
+
+impl MessageKey {
+    /// View this message key as a byte array.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rand_core::OsRng;
+
+    #[test]
+    fn ratchet_chains_match() {
+        let csprng: OsRng = OsRng;
+        let shared_key = [7u8; 32];
+
+        let bob_secret = ReusableSecret::new(csprng);
+        let bob_public = PublicKey::from(&bob_secret);
+
+        let (mut alice, mut alice_sending) =
+            DhRatchet::new_initiator(shared_key, &bob_public, csprng).unwrap();
+        let mut bob = DhRatchet::new_responder(shared_key, bob_secret);
+
+        // Bob receives Alice's first message and ratchets.
+        let (mut bob_receiving, mut bob_sending) = bob.ratchet(&alice.public_key(), csprng).unwrap();
+        assert_eq!(alice_sending.as_bytes(), bob_receiving.as_bytes());
+        assert_eq!(
+            alice_sending.next_message_key().as_bytes(),
+            bob_receiving.next_message_key().as_bytes()
+        );
+
+        // Alice receives Bob's reply and ratchets.
+        let (mut alice_receiving, _) = alice.ratchet(&bob.public_key(), csprng).unwrap();
+        assert_eq!(alice_receiving.as_bytes(), bob_sending.as_bytes());
+        assert_eq!(
+            alice_receiving.next_message_key().as_bytes(),
+            bob_sending.next_message_key().as_bytes()
+        );
+    }
+
+    #[test]
+    fn ratchet_rejects_invalid_public_keys() {
+        let csprng: OsRng = OsRng;
+        let shared_key = [7u8; 32];
+
+        let bob_secret = ReusableSecret::new(csprng);
+        let bob_public = PublicKey::from(&bob_secret);
+
+        let identity = PublicKey::from([0u8; 64]);
+        let mut off_curve = bob_public.to_bytes();
+        off_curve[32] ^= 1;
+        let off_curve = PublicKey::from(off_curve);
+
+        assert!(DhRatchet::new_initiator(shared_key, &identity, csprng).is_none());
+        assert!(DhRatchet::new_initiator(shared_key, &off_curve, csprng).is_none());
+
+        let mut bob = DhRatchet::new_responder(shared_key, bob_secret);
+        for invalid in [identity, off_curve].iter() {
+            assert!(bob.ratchet(invalid, csprng).is_none());
+            assert_eq!(bob.public_key(), bob_public);
+            assert_eq!(bob.their_public_key(), None);
+        }
+
+        // The rejected keys leave the ratchet usable.
+        let (alice, mut alice_sending) =
+            DhRatchet::new_initiator(shared_key, &bob_public, csprng).unwrap();
+        let (mut bob_receiving, _) = bob.ratchet(&alice.public_key(), csprng).unwrap();
+        assert_eq!(alice_sending.as_bytes(), bob_receiving.as_bytes());
+        assert_eq!(
+            alice_sending.next_message_key().as_bytes(),
+            bob_receiving.next_message_key().as_bytes()
+        );
+    }
+}
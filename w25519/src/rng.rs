@@ -0,0 +1,440 @@
+//! Adapters for RNGs implementing the traits of newer `rand_core` versions
+//!
+//! The key constructors such as [`StaticSecret::new`](crate::StaticSecret::new) take an RNG
+//! implementing the `rand_core` 0.5 `RngCore + CryptoRng` traits.  With the features
+//! `rand_core_06` or `rand_core_09`, the `from_rng` constructors such as
+//! [`StaticSecret::from_rng`](crate::StaticSecret::from_rng) accept an RNG of `rand_core` 0.5,
+//! 0.6 or 0.9 directly, see [`KeyRng`]:
+//!
+//! ```ignore
+//! let secret = StaticSecret::from_rng(rand_core_06::OsRng);
+//! let secret = StaticSecret::from_rng(rand_core_09::OsRng);
+//! ```
+//!
+//! Alternatively, wrapping an RNG in [`Rng06`] or [`Rng09`] makes it usable with the `new`
+//! constructors and anything else taking a `rand_core` 0.5 RNG:
+//!
+//! ```ignore
+//! let secret = StaticSecret::new(Rng06(&mut rng));
+//! // The rand_core 0.9 OsRng is fallible, i.e. only TryRngCore, so unwrap its errors
+//! let secret = StaticSecret::new(Rng09(rand_core_09::UnwrapErr(rand_core_09::OsRng)));
+//! ```
+//!
+//! Similarly, [`HalRng`] (feature `embedded_hal`) adapts a hardware TRNG peripheral
+//! implementing `embedded_hal::blocking::rng::Read`, running a [`HealthTest`] on its output
+//! before any of it is used.
+
+#[cfg(any(feature = "rand_core_06", feature = "embedded_hal"))]
+use core::num::NonZeroU32;
+
+use rand_core::{CryptoRng, Error, RngCore};
+
+/// An RNG of any supported `rand_core` version, as taken by the `from_rng` key constructors
+/// such as [`StaticSecret::from_rng`](crate::StaticSecret::from_rng).
+///
+/// The marker `M` names the `rand_core` version, [`RandCore05`], [`RandCore06`] or
+/// [`RandCore09`], which keeps the blanket implementations for the different versions apart.
+/// It is inferred from the RNG, unless the RNG implements the traits of several versions:
+///
+/// ```ignore
+/// let secret = StaticSecret::from_rng::<RandCore06, _>(&mut rng);
+/// ```
+#[cfg(any(feature = "rand_core_06", feature = "rand_core_09"))]
+pub trait KeyRng<M> {
+    /// Fill `dest` with random bytes.
+    ///
+    /// # Panics
+    ///
+    /// If a fallible RNG fails.
+    fn fill_key_bytes(&mut self, dest: &mut [u8]);
+}
+
+/// The [`KeyRng`] marker for `rand_core` 0.5 `RngCore + CryptoRng` RNGs.
+#[cfg(any(feature = "rand_core_06", feature = "rand_core_09"))]
+pub enum RandCore05 {}
+
+#[cfg(any(feature = "rand_core_06", feature = "rand_core_09"))]
+impl<R: RngCore + CryptoRng> KeyRng<RandCore05> for R {
+    fn fill_key_bytes(&mut self, dest: &mut [u8]) {
+        self.fill_bytes(dest)
+    }
+}
+
+/// The [`KeyRng`] marker for `rand_core` 0.6 `RngCore + CryptoRng` RNGs.
+#[cfg(feature = "rand_core_06")]
+pub enum RandCore06 {}
+
+#[cfg(feature = "rand_core_06")]
+impl<R: rand_core_06::RngCore + rand_core_06::CryptoRng> KeyRng<RandCore06> for R {
+    fn fill_key_bytes(&mut self, dest: &mut [u8]) {
+        self.fill_bytes(dest)
+    }
+}
+
+/// The [`KeyRng`] marker for `rand_core` 0.9 `TryCryptoRng` RNGs, which includes the
+/// infallible `CryptoRng`s and the fallible `OsRng`.
+#[cfg(feature = "rand_core_09")]
+pub enum RandCore09 {}
+
+#[cfg(feature = "rand_core_09")]
+impl<R: rand_core_09::TryCryptoRng> KeyRng<RandCore09> for R {
+    fn fill_key_bytes(&mut self, dest: &mut [u8]) {
+        if let Err(e) = self.try_fill_bytes(dest) {
+            panic!("RNG failure: {}", e);
+        }
+    }
+}
+
+/// Adapter for an RNG implementing the `rand_core` 0.6 `RngCore + CryptoRng` traits.
+#[cfg(feature = "rand_core_06")]
+pub struct Rng06<R>(pub R);
+
+#[cfg(feature = "rand_core_06")]
+impl<R: rand_core_06::RngCore> RngCore for Rng06<R> {
+    fn next_u32(&mut self) -> u32 {
+        self.0.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.0.try_fill_bytes(dest).map_err(|e| {
+            let code = e.code().or_else(|| NonZeroU32::new(Error::CUSTOM_START));
+            Error::from(code.expect("CUSTOM_START is nonzero"))
+        })
+    }
+}
+
+#[cfg(feature = "rand_core_06")]
+impl<R: rand_core_06::RngCore + rand_core_06::CryptoRng> CryptoRng for Rng06<R> {}
+
+/// Adapter for an RNG implementing the `rand_core` 0.9 `CryptoRng` trait.
+///
+/// A fallible RNG which only implements `TryCryptoRng`, such as the `rand_core` 0.9 `OsRng`,
+/// has to be wrapped in `rand_core_09::UnwrapErr` first, or passed to a `from_rng`
+/// constructor directly.
+#[cfg(feature = "rand_core_09")]
+pub struct Rng09<R>(pub R);
+
+#[cfg(feature = "rand_core_09")]
+impl<R: rand_core_09::RngCore> RngCore for Rng09<R> {
+    fn next_u32(&mut self) -> u32 {
+        self.0.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.0.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "rand_core_09")]
+impl<R: rand_core_09::CryptoRng> CryptoRng for Rng09<R> {}
+
+/// A health test run on the output of a hardware RNG before it is used.
+///
+/// Closures `FnMut(&[u8]) -> bool` can be used as ad hoc health tests.
+#[cfg(feature = "embedded_hal")]
+pub trait HealthTest {
+    /// Test a freshly read `sample`, returning `false` if the noise source has failed.
+    fn test(&mut self, sample: &[u8]) -> bool;
+}
+
+#[cfg(feature = "embedded_hal")]
+impl<F: FnMut(&[u8]) -> bool> HealthTest for F {
+    fn test(&mut self, sample: &[u8]) -> bool {
+        self(sample)
+    }
+}
+
+/// The error code of [`HalRng`] for a failed read from the peripheral.
+#[cfg(feature = "embedded_hal")]
+pub const READ_ERROR: u32 = Error::CUSTOM_START;
+
+/// The error code of [`HalRng`] for a failed health test.
+#[cfg(feature = "embedded_hal")]
+pub const HEALTH_TEST_FAILURE: u32 = Error::CUSTOM_START + 1;
+
+/// The repetition count test of NIST SP 800-90B section 4.4.1 on bytes: fails once the same
+/// byte is read `cutoff` times in a row, even across samples.
+///
+/// A failure is permanent, as SP 800-90B requires the noise source to be treated as broken
+/// until it is examined: every later sample fails too.  Create a new test to resume.
+#[cfg(feature = "embedded_hal")]
+pub struct RepetitionCountTest {
+    cutoff: usize,
+    last: Option<u8>,
+    count: usize,
+    failed: bool,
+}
+
+#[cfg(feature = "embedded_hal")]
+impl RepetitionCountTest {
+    /// Create a repetition count test with the given `cutoff`, which should be chosen from
+    /// the min-entropy of the noise source as described in SP 800-90B.
+    pub fn new(cutoff: usize) -> Self {
+        RepetitionCountTest {
+            cutoff,
+            last: None,
+            count: 0,
+            failed: false,
+        }
+    }
+}
+
+#[cfg(feature = "embedded_hal")]
+impl HealthTest for RepetitionCountTest {
+    fn test(&mut self, sample: &[u8]) -> bool {
+        for byte in sample {
+            if self.failed {
+                break;
+            }
+
+            if self.last == Some(*byte) {
+                self.count += 1;
+            } else {
+                self.last = Some(*byte);
+                self.count = 1;
+            }
+
+            self.failed = self.count >= self.cutoff;
+        }
+        !self.failed
+    }
+}
+
+/// Adapter for a hardware RNG implementing `embedded_hal::blocking::rng::Read`.
+///
+/// The peripheral has to be a cryptographically secure (true) RNG, since this adapter
+/// implements [`CryptoRng`] for it.  Every read is passed through the [`HealthTest`] before
+/// it is released; [`RngCore::try_fill_bytes`] reports read errors and health test failures
+/// as [`READ_ERROR`] and [`HEALTH_TEST_FAILURE`], while the infallible methods panic on them.
+#[cfg(feature = "embedded_hal")]
+pub struct HalRng<R, H> {
+    rng: R,
+    health_test: H,
+}
+
+#[cfg(feature = "embedded_hal")]
+impl<R: embedded_hal::blocking::rng::Read, H: HealthTest> HalRng<R, H> {
+    /// Wrap the peripheral `rng`, testing its output with `health_test`.
+    pub fn new(rng: R, health_test: H) -> Self {
+        HalRng { rng, health_test }
+    }
+
+    /// Release the wrapped peripheral.
+    pub fn into_inner(self) -> R {
+        self.rng
+    }
+}
+
+#[cfg(feature = "embedded_hal")]
+impl<R: embedded_hal::blocking::rng::Read, H: HealthTest> RngCore for HalRng<R, H> {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        if let Err(e) = self.try_fill_bytes(dest) {
+            panic!("hardware RNG failure: {}", e);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        let code = if self.rng.read(dest).is_err() {
+            READ_ERROR
+        } else if !self.health_test.test(dest) {
+            HEALTH_TEST_FAILURE
+        } else {
+            return Ok(());
+        };
+
+        // Do not leave output of a failed noise source around
+        dest.iter_mut().for_each(|b| *b = 0);
+        Err(Error::from(NonZeroU32::new(code).expect("CUSTOM_START is nonzero")))
+    }
+}
+
+#[cfg(feature = "embedded_hal")]
+impl<R: embedded_hal::blocking::rng::Read, H: HealthTest> CryptoRng for HalRng<R, H> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::w25519::StaticSecret;
+
+    /// A deterministic RNG returning a fixed byte, standing in for an RNG of a newer
+    /// `rand_core` version.
+    struct FixedRng(u8);
+
+    #[cfg(feature = "rand_core_06")]
+    impl rand_core_06::RngCore for FixedRng {
+        fn next_u32(&mut self) -> u32 {
+            rand_core_06::impls::next_u32_via_fill(self)
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            rand_core_06::impls::next_u64_via_fill(self)
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            dest.iter_mut().for_each(|b| *b = self.0);
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core_06::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "rand_core_06")]
+    impl rand_core_06::CryptoRng for FixedRng {}
+
+    #[cfg(feature = "rand_core_09")]
+    impl rand_core_09::RngCore for FixedRng {
+        fn next_u32(&mut self) -> u32 {
+            rand_core_09::impls::next_u32_via_fill(self)
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            rand_core_09::impls::next_u64_via_fill(self)
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            dest.iter_mut().for_each(|b| *b = self.0);
+        }
+    }
+
+    #[cfg(feature = "rand_core_09")]
+    impl rand_core_09::CryptoRng for FixedRng {}
+
+    /// A fallible `rand_core` 0.9 RNG like `OsRng`, which only implements `TryCryptoRng`.
+    #[cfg(feature = "rand_core_09")]
+    struct FallibleRng(u8);
+
+    #[cfg(feature = "rand_core_09")]
+    impl rand_core_09::TryRngCore for FallibleRng {
+        type Error = core::convert::Infallible;
+
+        fn try_next_u32(&mut self) -> Result<u32, Self::Error> {
+            Ok(u32::from_le_bytes([self.0; 4]))
+        }
+
+        fn try_next_u64(&mut self) -> Result<u64, Self::Error> {
+            Ok(u64::from_le_bytes([self.0; 8]))
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Self::Error> {
+            dest.iter_mut().for_each(|b| *b = self.0);
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "rand_core_09")]
+    impl rand_core_09::TryCryptoRng for FallibleRng {}
+
+    #[cfg(feature = "embedded_hal")]
+    impl embedded_hal::blocking::rng::Read for FixedRng {
+        type Error = ();
+
+        fn read(&mut self, buffer: &mut [u8]) -> Result<(), ()> {
+            buffer.iter_mut().for_each(|b| *b = self.0);
+            Ok(())
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "embedded_hal")]
+    fn hal_rng_health_test() {
+        let secret = StaticSecret::new(HalRng::new(FixedRng(0x24), |_: &[u8]| true));
+        assert_eq!(secret.to_bytes(), StaticSecret::from([0x24; 32]).to_bytes());
+
+        let mut rng = HalRng::new(FixedRng(0x24), RepetitionCountTest::new(8));
+        let mut bytes = [0xffu8; 32];
+        let err = rng.try_fill_bytes(&mut bytes).unwrap_err();
+        assert_eq!(err.code().map(|c| c.get()), Some(HEALTH_TEST_FAILURE));
+        assert_eq!(bytes, [0u8; 32]);
+    }
+
+    #[test]
+    #[cfg(feature = "embedded_hal")]
+    fn repetition_count_test_failure_is_sticky() {
+        let mut test = RepetitionCountTest::new(4);
+        assert!(test.test(&[1, 2, 2, 2]));
+        assert!(!test.test(&[2, 3, 4, 5]));
+
+        // Neither a sample without repetitions nor the next bytes recover the test.
+        assert!(!test.test(&[6, 7, 8, 9]));
+        assert!(!test.test(&[]));
+
+        assert!(RepetitionCountTest::new(4).test(&[6, 7, 8, 9]));
+    }
+
+    #[test]
+    #[cfg(feature = "rand_core_06")]
+    fn rng06_static_secret() {
+        let secret = StaticSecret::new(Rng06(FixedRng(0x24)));
+
+        assert_eq!(secret.to_bytes(), StaticSecret::from([0x24; 32]).to_bytes());
+    }
+
+    #[test]
+    #[cfg(feature = "rand_core_09")]
+    fn rng09_static_secret() {
+        let mut rng = FixedRng(0x42);
+        let secret = StaticSecret::new(Rng09(&mut rng));
+
+        assert_eq!(secret.to_bytes(), StaticSecret::from([0x42; 32]).to_bytes());
+    }
+
+    #[test]
+    #[cfg(feature = "rand_core_06")]
+    fn from_rng_rand_core_06() {
+        let secret = StaticSecret::from_rng::<RandCore06, _>(FixedRng(0x24));
+        assert_eq!(secret.to_bytes(), StaticSecret::from([0x24; 32]).to_bytes());
+
+        let public = crate::PublicKey::from(&crate::EphemeralSecret::from_rng::<RandCore06, _>(FixedRng(0x24)));
+        assert_eq!(public, crate::PublicKey::from(&secret));
+    }
+
+    #[test]
+    #[cfg(feature = "rand_core_09")]
+    fn from_rng_rand_core_09() {
+        let mut rng = FixedRng(0x42);
+        let secret = StaticSecret::from_rng::<RandCore09, _>(&mut rng);
+        assert_eq!(secret.to_bytes(), StaticSecret::from([0x42; 32]).to_bytes());
+
+        // A fallible RNG like the rand_core 0.9 OsRng, with the marker inferred
+        let secret = StaticSecret::from_rng(FallibleRng(0x42));
+        assert_eq!(secret.to_bytes(), StaticSecret::from([0x42; 32]).to_bytes());
+
+        let secret = StaticSecret::new(Rng09(rand_core_09::UnwrapErr(FallibleRng(0x42))));
+        assert_eq!(secret.to_bytes(), StaticSecret::from([0x42; 32]).to_bytes());
+    }
+
+    #[test]
+    #[cfg(any(feature = "rand_core_06", feature = "rand_core_09"))]
+    fn from_rng_rand_core_05() {
+        let secret = StaticSecret::from_rng(rand_core::OsRng);
+        let _ = crate::PublicKey::from(&secret);
+    }
+}
@@ -0,0 +1,159 @@
+//! Forward-secure rotation of a long-lived secret key
+//!
+//! A [`RatchetingSecret`] is a hash chain of states, each of which determines the
+//! [`StaticSecret`] of one epoch:
+//!
+//! ```text
+//! key_i       = clamp(SHA-256("w25519 ratcheting key" || state_i))
+//! state_{i+1} = SHA-256("w25519 ratcheting state" || state_i)
+//! ```
+//!
+//! [`RatchetingSecret::ratchet`] moves to the next epoch and wipes the previous state and key,
+//! so a compromise of the current state does not reveal the keys of earlier epochs.  Later
+//! epochs are of course derivable from a compromised state, so rotate to a fresh
+//! [`RatchetingSecret::new`] to recover from a compromise.
+
+use curve25519_dalek::digest::Digest;
+use sha2::Sha256;
+use x25519_dalek::SharedSecret;
+
+use rand_core::CryptoRng;
+use rand_core::RngCore;
+
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::w25519::{PublicKey, StaticSecret};
+
+/// A long-lived secret key which deterministically evolves from epoch to epoch.
+#[derive(Zeroize)]
+#[zeroize(drop)]
+pub struct RatchetingSecret {
+    state: [u8; 32],
+    epoch: u64,
+    secret: StaticSecret,
+}
+
+impl RatchetingSecret {
+    /// Generate a new ratcheting secret starting at epoch 0.
+    pub fn new<T: RngCore + CryptoRng>(mut csprng: T) -> Self {
+        let mut state = [0u8; 32];
+        csprng.fill_bytes(&mut state);
+
+        let ratcheting = RatchetingSecret::from_state(state, 0);
+        state.zeroize();
+        ratcheting
+    }
+
+    /// Restore a ratcheting secret from its stored state and epoch, see
+    /// [`RatchetingSecret::to_state`].
+    pub fn from_state(state: [u8; 32], epoch: u64) -> Self {
+        RatchetingSecret {
+            state,
+            epoch,
+            secret: key(&state),
+        }
+    }
+
+    /// Extract the state of the current epoch for storage.
+    ///
+    /// The state determines the keys of the current and all later epochs.
+    pub fn to_state(&self) -> [u8; 32] {
+        self.state
+    }
+
+    /// Extract the state of the current epoch for storage, wiping it when it is dropped.
+    pub fn to_state_zeroizing(&self) -> Zeroizing<[u8; 32]> {
+        Zeroizing::new(self.state)
+    }
+
+    /// The current epoch, counting the calls to [`RatchetingSecret::ratchet`].
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// The secret key of the current epoch.
+    pub fn secret(&self) -> &StaticSecret {
+        &self.secret
+    }
+
+    /// The public key of the current epoch.
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey::from(&self.secret)
+    }
+
+    /// The public key of the next epoch, e.g. to announce it ahead of a rotation.
+    pub fn next_public_key(&self) -> PublicKey {
+        let mut next = next_state(&self.state);
+        let public = PublicKey::from(&key(&next));
+        next.zeroize();
+        public
+    }
+
+    /// Perform a Diffie-Hellman key agreement with the secret key of the current epoch.
+    pub fn diffie_hellman(&self, their_public: &PublicKey) -> SharedSecret {
+        self.secret.diffie_hellman(their_public)
+    }
+
+    /// Move to the next epoch, wiping the state and secret key of the current one.
+    ///
+    /// # Panics
+    ///
+    /// If the epoch counter overflows.
+    pub fn ratchet(&mut self) {
+        let mut next = next_state(&self.state);
+        self.state.zeroize();
+
+        self.secret = key(&next);
+        self.state = next;
+        self.epoch = self.epoch.checked_add(1).expect("epoch counter overflow");
+        next.zeroize();
+    }
+}
+
+fn key(state: &[u8; 32]) -> StaticSecret {
+    let mut bytes: [u8; 32] = Sha256::new()
+        .chain(b"w25519 ratcheting key")
+        .chain(state)
+        .finalize()
+        .into();
+
+    let secret = StaticSecret::from(bytes);
+    bytes.zeroize();
+    secret
+}
+
+fn next_state(state: &[u8; 32]) -> [u8; 32] {
+    Sha256::new()
+        .chain(b"w25519 ratcheting state")
+        .chain(state)
+        .finalize()
+        .into()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rand_core::OsRng;
+
+    #[test]
+    fn ratcheting_secret_evolves() {
+        let mut ratcheting = RatchetingSecret::new(OsRng);
+        let restored = RatchetingSecret::from_state(ratcheting.to_state(), ratcheting.epoch());
+
+        let first = ratcheting.public_key();
+        let announced = ratcheting.next_public_key();
+        assert_eq!(restored.public_key(), first);
+
+        ratcheting.ratchet();
+        assert_eq!(ratcheting.epoch(), 1);
+        assert_eq!(ratcheting.public_key(), announced);
+        assert_ne!(ratcheting.public_key(), first);
+
+        let peer = StaticSecret::new(OsRng);
+        assert_eq!(
+            ratcheting.diffie_hellman(&PublicKey::from(&peer)).to_bytes(),
+            peer.diffie_hellman(&announced).to_bytes()
+        );
+    }
+}
@@ -0,0 +1,386 @@
+//! Extensions for the [`SharedSecret`] resulting from a w25519 key exchange
+
+use curve25519_dalek::digest::Digest;
+use curve25519_dalek::weierstrass::WeierstrassPoint;
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac, NewMac};
+use sha2::Sha256;
+use x25519_dalek::SharedSecret;
+
+use zeroize::Zeroize;
+
+/// The role of a party in a key exchange.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Role {
+    /// The party `U` which initiated the key exchange.
+    Initiator,
+    /// The party `V` which responded to the key exchange.
+    Responder,
+}
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for x25519_dalek::SharedSecret {}
+}
+
+/// Additional operations on a [`SharedSecret`].
+///
+/// This trait is sealed and only implemented for [`SharedSecret`].
+pub trait SharedSecretExt: private::Sealed {
+    /// Compute an SP 800-56A style bilateral key confirmation tag, proving to the
+    /// counterparty that `role` derived this shared secret.
+    ///
+    /// `initiator_id` and `responder_id` identify the initiator `U` and responder `V` and have
+    /// to be the same for both parties.  The tag is computed as
+    ///
+    /// ```text
+    /// MacKey = HKDF-SHA-256(salt = none, IKM = Z, info = "w25519 key confirmation")
+    /// MacTag = HMAC-SHA-256(MacKey, "KC_2_U" || len(ID_U) || ID_U || len(ID_V) || ID_V)
+    /// ```
+    ///
+    /// for the initiator, where `len` is the 4-byte big-endian length of the identifier.  The
+    /// responder uses the message string `"KC_2_V"` and swaps the identifiers.
+    fn confirmation_tag(&self, role: Role, initiator_id: &[u8], responder_id: &[u8]) -> [u8; 32];
+
+    /// Verify in constant time a key confirmation tag which was computed by the
+    /// counterparty acting as `role`, see [`SharedSecretExt::confirmation_tag`].
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the counterparty derived the same shared secret.
+    #[must_use]
+    fn verify_confirmation_tag(
+        &self,
+        role: Role,
+        initiator_id: &[u8],
+        responder_id: &[u8],
+        tag: &[u8; 32],
+    ) -> bool;
+
+    /// Encode this shared secret as the big-endian x-coordinate of the shared point on
+    /// Wei25519, the shared secret value `Z` of IEEE 1363 and SEC 1 ECDH.
+    ///
+    /// Use this instead of [`SharedSecret::as_bytes`], which is the little-endian Montgomery
+    /// u-coordinate, when agreeing on a key with a standards-compliant Weierstrass peer.  A
+    /// non-contributory shared secret (see [`SharedSecret::was_contributory`]) is encoded as
+    /// zero.
+    fn to_sec1_x_bytes(&self) -> [u8; 32];
+
+    /// Derive key material from this shared secret with the ANSI X9.63 KDF, filling `output`.
+    ///
+    /// The output is the concatenation of the blocks `D(Z || counter || shared_info)` for a
+    /// 4-byte big-endian `counter` starting at 1, where `Z` is the SEC 1 shared secret value of
+    /// [`SharedSecretExt::to_sec1_x_bytes`], as used by X9.63 and ECIES peers.
+    ///
+    /// # Panics
+    ///
+    /// If `output` is longer than `(2^32 - 1)` hash blocks.
+    fn x963_kdf<D: Digest>(&self, shared_info: &[u8], output: &mut [u8]);
+
+    /// Derive key material from this shared secret with the NIST SP 800-56C single-step
+    /// concatenation KDF (ConcatKDF) using the hash function `D`, filling `output`.
+    ///
+    /// The output is the concatenation of the blocks `D(counter || Z || other_info)` for a
+    /// 4-byte big-endian `counter` starting at 1, where `Z` is the big-endian x-coordinate of
+    /// [`SharedSecretExt::to_sec1_x_bytes`], as SP 800-56A specifies for ECC schemes.
+    ///
+    /// # Panics
+    ///
+    /// If `output` is longer than `(2^32 - 1)` hash blocks.
+    fn concat_kdf<D: Digest>(&self, other_info: &[u8], output: &mut [u8]);
+
+    /// Derive a channel-binding value bound to this shared secret and the `transcript_hash` of
+    /// the handshake which produced it, similar to a TLS 1.3 exporter, filling `output`.
+    ///
+    /// Both parties have to supply the same transcript hash, `label` and `context`.  The value
+    /// is computed as
+    ///
+    /// ```text
+    /// PRK    = HKDF-Extract-SHA-256(salt = transcript_hash, IKM = Z)
+    /// output = HKDF-Expand-SHA-256(PRK, "w25519 exporter" || L || len(label) || label
+    ///                                   || len(context) || context, L)
+    /// ```
+    ///
+    /// where `L` is the 2-byte big-endian length of `output` and `len` the 4-byte big-endian
+    /// length of the following field, so exports of different lengths are independent.
+    ///
+    /// # Panics
+    ///
+    /// If `output` is longer than 8160 bytes, the maximum output length of HKDF-SHA-256.
+    fn exporter(&self, transcript_hash: &[u8], label: &[u8], context: &[u8], output: &mut [u8]);
+}
+
+impl SharedSecretExt for SharedSecret {
+    fn confirmation_tag(&self, role: Role, initiator_id: &[u8], responder_id: &[u8]) -> [u8; 32] {
+        confirmation_mac(self, role, initiator_id, responder_id)
+            .finalize()
+            .into_bytes()
+            .into()
+    }
+
+    fn verify_confirmation_tag(
+        &self,
+        role: Role,
+        initiator_id: &[u8],
+        responder_id: &[u8],
+        tag: &[u8; 32],
+    ) -> bool {
+        confirmation_mac(self, role, initiator_id, responder_id)
+            .verify(tag)
+            .is_ok()
+    }
+
+    fn to_sec1_x_bytes(&self) -> [u8; 32] {
+        let mut x = WeierstrassPoint::from_montgomery(self.to_bytes(), [0; 32]).x;
+        x.reverse();
+        x
+    }
+
+    fn x963_kdf<D: Digest>(&self, shared_info: &[u8], output: &mut [u8]) {
+        let mut z = self.to_sec1_x_bytes();
+        kdf_x963::<D>(&z, shared_info, output);
+        z.zeroize();
+    }
+
+    fn concat_kdf<D: Digest>(&self, other_info: &[u8], output: &mut [u8]) {
+        let mut z = self.to_sec1_x_bytes();
+        kdf_concat::<D>(&z, other_info, output);
+        z.zeroize();
+    }
+
+    fn exporter(&self, transcript_hash: &[u8], label: &[u8], context: &[u8], output: &mut [u8]) {
+        assert!(output.len() <= 255 * 32, "requested exporter output is too long");
+
+        Hkdf::<Sha256>::new(Some(transcript_hash), self.as_bytes())
+            .expand_multi_info(
+                &[
+                    b"w25519 exporter",
+                    &(output.len() as u16).to_be_bytes(),
+                    &(label.len() as u32).to_be_bytes(),
+                    label,
+                    &(context.len() as u32).to_be_bytes(),
+                    context,
+                ],
+                output,
+            )
+            .expect("the output length is checked above");
+    }
+}
+
+/// The ANSI X9.63 KDF over the shared secret value `z`, see [`SharedSecretExt::x963_kdf`].
+fn kdf_x963<D: Digest>(z: &[u8], shared_info: &[u8], output: &mut [u8]) {
+    for (i, block) in output.chunks_mut(D::output_size()).enumerate() {
+        let mut hash = D::new();
+        hash.update(z);
+        hash.update(kdf_counter(i).to_be_bytes());
+        hash.update(shared_info);
+
+        block.copy_from_slice(&hash.finalize()[..block.len()]);
+    }
+}
+
+/// The SP 800-56C ConcatKDF over the shared secret value `z`, see
+/// [`SharedSecretExt::concat_kdf`].
+fn kdf_concat<D: Digest>(z: &[u8], other_info: &[u8], output: &mut [u8]) {
+    for (i, block) in output.chunks_mut(D::output_size()).enumerate() {
+        let mut hash = D::new();
+        hash.update(kdf_counter(i).to_be_bytes());
+        hash.update(z);
+        hash.update(other_info);
+
+        block.copy_from_slice(&hash.finalize()[..block.len()]);
+    }
+}
+
+/// The 32-bit counter of the hash-based KDFs for the block with index `i`.
+fn kdf_counter(i: usize) -> u32 {
+    assert!(i < u32::MAX as usize, "requested KDF output is too long");
+
+    i as u32 + 1
+}
+
+/// Key the confirmation MAC and feed it the `MacData` for `role`.
+fn confirmation_mac(
+    shared_secret: &SharedSecret,
+    role: Role,
+    initiator_id: &[u8],
+    responder_id: &[u8],
+) -> Hmac<Sha256> {
+    let mut mac_key = [0u8; 32];
+    Hkdf::<Sha256>::new(None, shared_secret.as_bytes())
+        .expand(b"w25519 key confirmation", &mut mac_key)
+        .expect("32 bytes is a valid HKDF-SHA-256 output length");
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(&mac_key)
+        .expect("HMAC accepts keys of any length");
+    mac_key.zeroize();
+
+    let (message, provider_id, recipient_id) = match role {
+        Role::Initiator => (b"KC_2_U", initiator_id, responder_id),
+        Role::Responder => (b"KC_2_V", responder_id, initiator_id),
+    };
+
+    mac.update(message);
+    mac.update(&(provider_id.len() as u32).to_be_bytes());
+    mac.update(provider_id);
+    mac.update(&(recipient_id.len() as u32).to_be_bytes());
+    mac.update(recipient_id);
+
+    mac
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::w25519::{PublicKey, StaticSecret};
+
+    use curve25519_dalek::montgomery::MontgomeryPoint;
+
+    use rand_core::OsRng;
+
+    #[test]
+    fn key_confirmation() {
+        let csprng: OsRng = OsRng;
+
+        let a_secret = StaticSecret::new(csprng);
+        let b_secret = StaticSecret::new(csprng);
+
+        let a_shared = a_secret.diffie_hellman(&PublicKey::from(&b_secret));
+        let b_shared = b_secret.diffie_hellman(&PublicKey::from(&a_secret));
+
+        let a_tag = a_shared.confirmation_tag(Role::Initiator, b"alice", b"bob");
+        let b_tag = b_shared.confirmation_tag(Role::Responder, b"alice", b"bob");
+
+        assert_ne!(a_tag, b_tag);
+        assert!(b_shared.verify_confirmation_tag(Role::Initiator, b"alice", b"bob", &a_tag));
+        assert!(a_shared.verify_confirmation_tag(Role::Responder, b"alice", b"bob", &b_tag));
+
+        assert!(!b_shared.verify_confirmation_tag(Role::Responder, b"alice", b"bob", &a_tag));
+        assert!(!b_shared.verify_confirmation_tag(Role::Initiator, b"alice", b"eve", &a_tag));
+
+        let c_shared = a_secret.diffie_hellman(&PublicKey::from(&a_secret));
+        assert!(!c_shared.verify_confirmation_tag(Role::Initiator, b"alice", b"bob", &a_tag));
+    }
+
+    #[test]
+    fn sec1_x_bytes() {
+        let csprng: OsRng = OsRng;
+
+        let a_secret = StaticSecret::new(csprng);
+        let b_public = PublicKey::from(&StaticSecret::new(csprng));
+
+        let shared = a_secret.diffie_hellman(&b_public);
+        let mut expected = (a_secret.0 * b_public.0).canonical().x;
+        expected.reverse();
+
+        assert_eq!(shared.to_sec1_x_bytes(), expected);
+    }
+
+    #[test]
+    fn exporter() {
+        let csprng: OsRng = OsRng;
+
+        let a_secret = StaticSecret::new(csprng);
+        let b_secret = StaticSecret::new(csprng);
+
+        let a_shared = a_secret.diffie_hellman(&PublicKey::from(&b_secret));
+        let b_shared = b_secret.diffie_hellman(&PublicKey::from(&a_secret));
+
+        let transcript_hash = Sha256::digest(b"w25519 test transcript");
+        let mut a_value = [0u8; 32];
+        let mut b_value = [0u8; 32];
+        a_shared.exporter(&transcript_hash, b"EXPORTER-test", b"", &mut a_value);
+        b_shared.exporter(&transcript_hash, b"EXPORTER-test", b"", &mut b_value);
+        assert_eq!(a_value, b_value);
+
+        // The value depends on the transcript, label, context and length.
+        let mut other = [0u8; 32];
+        b_shared.exporter(&[0u8; 32], b"EXPORTER-test", b"", &mut other);
+        assert_ne!(a_value, other);
+        b_shared.exporter(&transcript_hash, b"EXPORTER-other", b"", &mut other);
+        assert_ne!(a_value, other);
+        b_shared.exporter(&transcript_hash, b"EXPORTER-test", b"context", &mut other);
+        assert_ne!(a_value, other);
+
+        let mut short = [0u8; 16];
+        b_shared.exporter(&transcript_hash, b"EXPORTER-test", b"", &mut short);
+        assert_ne!(a_value[..16], short);
+    }
+
+    #[test]
+    fn x963_kdf() {
+        let shared = SharedSecret::new(MontgomeryPoint([0x11; 32]));
+        let mut output = [0u8; 48];
+        shared.x963_kdf::<Sha256>(b"w25519 test", &mut output);
+
+        let expected = [
+            0xa3, 0x3f, 0xc2, 0xea, 0x4d, 0x68, 0x20, 0x4e, 0xae, 0x43, 0x63, 0xca, 0x3a, 0xd2, 0xa2, 0xa1,
+            0x80, 0x90, 0xb4, 0xc8, 0x0e, 0xa5, 0xbc, 0xb6, 0xf9, 0x2c, 0xc0, 0x46, 0x05, 0x5b, 0x3c, 0x3a,
+            0x59, 0xe1, 0x5c, 0x13, 0x17, 0x1c, 0x95, 0x03, 0xb3, 0x43, 0x25, 0x57, 0x55, 0x4d, 0x2a, 0xf8,
+        ];
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn concat_kdf() {
+        let shared = SharedSecret::new(MontgomeryPoint([0x11; 32]));
+        let mut output = [0u8; 48];
+        shared.concat_kdf::<Sha256>(b"w25519 test", &mut output);
+
+        let expected = [
+            0xdc, 0xd0, 0x57, 0xbd, 0x11, 0x7a, 0x94, 0x52, 0x52, 0x8f, 0xf6, 0x8e, 0xce, 0x32, 0xfb, 0xc1,
+            0x83, 0x08, 0xb1, 0xb8, 0x2b, 0xfd, 0x21, 0x0c, 0xa4, 0xc0, 0x80, 0xd1, 0x8f, 0x5e, 0xa3, 0xf9,
+            0x3b, 0xac, 0x17, 0xa2, 0x64, 0x39, 0x4a, 0xbf, 0x63, 0x97, 0xb4, 0x65, 0xdb, 0x9c, 0x6b, 0x60,
+        ];
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn x963_kdf_nist_vector() {
+        // NIST CAVS ANSI X9.63 KDF, SHA-256, 192-bit Z, 128-bit SharedInfo, 1024-bit key
+        let z = [
+            0x22, 0x51, 0x8b, 0x10, 0xe7, 0x0f, 0x2a, 0x3f, 0x24, 0x38, 0x10, 0xae, 0x32, 0x54, 0x13, 0x9e,
+            0xfb, 0xee, 0x04, 0xaa, 0x57, 0xc7, 0xaf, 0x7d,
+        ];
+        let shared_info = [
+            0x75, 0xee, 0xf8, 0x1a, 0xa3, 0x04, 0x1e, 0x33, 0xb8, 0x09, 0x71, 0x20, 0x3d, 0x2c, 0x0c, 0x52,
+        ];
+        let mut output = [0u8; 128];
+        kdf_x963::<Sha256>(&z, &shared_info, &mut output);
+
+        let expected = [
+            0xc4, 0x98, 0xaf, 0x77, 0x16, 0x1c, 0xc5, 0x9f, 0x29, 0x62, 0xb9, 0xa7, 0x13, 0xe2, 0xb2, 0x15,
+            0x15, 0x2d, 0x13, 0x97, 0x66, 0xce, 0x34, 0xa7, 0x76, 0xdf, 0x11, 0x86, 0x6a, 0x69, 0xbf, 0x2e,
+            0x52, 0xa1, 0x3d, 0x9c, 0x7c, 0x6f, 0xc8, 0x78, 0xc5, 0x0c, 0x5e, 0xa0, 0xbc, 0x7b, 0x00, 0xe0,
+            0xda, 0x24, 0x47, 0xcf, 0xd8, 0x74, 0xf6, 0xcf, 0x92, 0xf3, 0x0d, 0x00, 0x97, 0x11, 0x14, 0x85,
+            0x50, 0x0c, 0x90, 0xc3, 0xaf, 0x8b, 0x48, 0x78, 0x72, 0xd0, 0x46, 0x85, 0xd1, 0x4c, 0x8d, 0x1d,
+            0xc8, 0xd7, 0xfa, 0x08, 0xbe, 0xb0, 0xce, 0x0a, 0xba, 0xbc, 0x11, 0xf0, 0xbd, 0x49, 0x62, 0x69,
+            0x14, 0x2d, 0x43, 0x52, 0x5a, 0x78, 0xe5, 0xbc, 0x79, 0xa1, 0x7f, 0x59, 0x67, 0x6a, 0x57, 0x06,
+            0xdc, 0x54, 0xd5, 0x4d, 0x4d, 0x1f, 0x0b, 0xd7, 0xe3, 0x86, 0x12, 0x8e, 0xc2, 0x6a, 0xfc, 0x21,
+        ];
+        assert_eq!(output[..], expected[..]);
+    }
+
+    #[test]
+    fn concat_kdf_nist_vector() {
+        // NIST SP 800-56C single-step KDF, SHA-256, 256-bit Z, 128-bit key
+        let z = [
+            0x52, 0x16, 0x9a, 0xf5, 0xc4, 0x85, 0xdc, 0xc2, 0x32, 0x1e, 0xb8, 0xd2, 0x6d, 0x5e, 0xfa, 0x21,
+            0xfb, 0x9b, 0x93, 0xc9, 0x8e, 0x38, 0x41, 0x2e, 0xe2, 0x48, 0x4c, 0xf1, 0x4f, 0x0d, 0x0d, 0x23,
+        ];
+        let other_info = [
+            0xa1, 0xb2, 0xc3, 0xd4, 0xe5, 0x37, 0x28, 0x15, 0x7e, 0x63, 0x46, 0x12, 0xc1, 0x2d, 0x6d, 0x52,
+            0x23, 0xe2, 0x04, 0xae, 0xea, 0x43, 0x41, 0x56, 0x53, 0x69, 0x64, 0x7b, 0xd1, 0x84, 0xbc, 0xd2,
+            0x46, 0xf7, 0x29, 0x71, 0xf2, 0x92, 0xba, 0xda, 0xa2, 0xfe, 0x41, 0x24, 0x61, 0x2c, 0xba,
+        ];
+        let mut output = [0u8; 16];
+        kdf_concat::<Sha256>(&z, &other_info, &mut output);
+
+        let expected = [
+            0x1c, 0x3b, 0xc9, 0xe7, 0xc4, 0x54, 0x7c, 0x51, 0x91, 0xc0, 0xd4, 0x78, 0xcc, 0xca, 0xed, 0x55,
+        ];
+        assert_eq!(output, expected);
+    }
+}
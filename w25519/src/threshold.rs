@@ -0,0 +1,187 @@
+//! Two-party threshold Diffie-Hellman
+//!
+//! A [`StaticSecret`] can be split into two additive [`SecretShare`]s held by different
+//! parties.  Each party computes a [`PartialSharedSecret`] with its share, and the two partial
+//! results combine via point addition into the same [`SharedSecret`] which
+//! [`StaticSecret::diffie_hellman`] would produce, without either party ever holding the
+//! whole secret key.
+//!
+//! Since a clamped secret key `s` is a multiple of the cofactor, the shares are taken of
+//! `s / 8 mod l` and applied to `8 * their_public`, so that the combined result matches the
+//! unsplit key even for public keys with a small-order component.
+//!
+//! Each share is long-lived, so the peer's public key and the partial results are validated:
+//! points which are not on Wei25519, the point at infinity and points of small order are
+//! rejected.
+
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::weierstrass::WeierstrassPoint;
+use x25519_dalek::SharedSecret;
+
+use rand_core::CryptoRng;
+use rand_core::RngCore;
+
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::w25519::{PublicKey, StaticSecret};
+
+/// One of the two additive shares of a [`StaticSecret`].
+#[derive(Clone, Zeroize)]
+#[zeroize(drop)]
+pub struct SecretShare(Scalar);
+
+/// Split `secret` into two [`SecretShare`]s, using `csprng` to randomize the sharing.
+pub fn split<T: RngCore + CryptoRng>(secret: &StaticSecret, mut csprng: T) -> (SecretShare, SecretShare) {
+    let mut t = secret.0.reduce() * Scalar::from(8u64).invert();
+
+    let t1 = Scalar::random(&mut csprng);
+    let t2 = t - t1;
+    t.zeroize();
+
+    (SecretShare(t1), SecretShare(t2))
+}
+
+impl SecretShare {
+    /// Compute this share's contribution to the Diffie-Hellman key agreement with
+    /// `their_public`.
+    ///
+    /// Returns `None` if `their_public` is not on Wei25519 or of small order, or if the
+    /// contribution is the point at infinity.
+    pub fn partial_diffie_hellman(&self, their_public: &PublicKey) -> Option<PartialSharedSecret> {
+        if !is_valid(&their_public.0) {
+            return None;
+        }
+
+        let cleared = their_public.0.double().double().double();
+        if !is_valid(&cleared) {
+            return None;
+        }
+
+        PartialSharedSecret::new(self.0 * cleared)
+    }
+
+    /// Extract this share's bytes for storage.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_bytes()
+    }
+
+    /// Extract this share's bytes for storage, wiping them when they are dropped.
+    pub fn to_bytes_zeroizing(&self) -> Zeroizing<[u8; 32]> {
+        Zeroizing::new(self.to_bytes())
+    }
+}
+
+impl From<[u8; 32]> for SecretShare {
+    /// Load a secret share from a byte array.
+    fn from(bytes: [u8; 32]) -> SecretShare {
+        SecretShare(Scalar::from_bytes_mod_order(bytes))
+    }
+}
+
+/// The contribution of one [`SecretShare`] to a Diffie-Hellman key agreement.
+///
+/// This has to be kept as secret as the resulting [`SharedSecret`] while it is sent to the
+/// party combining both contributions.
+#[derive(Zeroize)]
+#[zeroize(drop)]
+pub struct PartialSharedSecret(WeierstrassPoint);
+
+impl PartialSharedSecret {
+    fn new(point: WeierstrassPoint) -> Option<PartialSharedSecret> {
+        if is_valid(&point) {
+            Some(PartialSharedSecret(point))
+        } else {
+            None
+        }
+    }
+
+    /// Load a partial shared secret from a byte array, see [`PartialSharedSecret::to_bytes`].
+    ///
+    /// Returns `None` if the point is not on Wei25519 or is the point at infinity.
+    pub fn from_bytes(bytes: [u8; 64]) -> Option<PartialSharedSecret> {
+        PartialSharedSecret::new(WeierstrassPoint::from(bytes))
+    }
+
+    /// Convert this partial shared secret to a byte array.
+    pub fn to_bytes(&self) -> [u8; 64] {
+        self.0.to_bytes()
+    }
+
+    /// Convert this partial shared secret to a byte array which is wiped when it is dropped.
+    pub fn to_bytes_zeroizing(&self) -> Zeroizing<[u8; 64]> {
+        Zeroizing::new(self.to_bytes())
+    }
+}
+
+/// Combine the contributions of both shares into the full [`SharedSecret`].
+///
+/// Returns `None` if a contribution or the combined point is not on Wei25519 or is the point
+/// at infinity.
+pub fn combine(a: &PartialSharedSecret, b: &PartialSharedSecret) -> Option<SharedSecret> {
+    if !is_valid(&a.0) || !is_valid(&b.0) {
+        return None;
+    }
+
+    let shared = PartialSharedSecret::new(a.0 + b.0)?;
+    Some(SharedSecret::new(shared.0.into_montgomery_compressed()))
+}
+
+fn is_valid(point: &WeierstrassPoint) -> bool {
+    point.is_on_curve() && *point != WeierstrassPoint::default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rand_core::OsRng;
+
+    #[test]
+    fn threshold_matches_diffie_hellman() {
+        let csprng: OsRng = OsRng;
+
+        let secret = StaticSecret::new(csprng);
+        let their_secret = StaticSecret::new(csprng);
+        let their_public = PublicKey::from(&their_secret);
+
+        let (a, b) = split(&secret, csprng);
+        let b = SecretShare::from(b.to_bytes());
+
+        let partial_a = a.partial_diffie_hellman(&their_public).unwrap();
+        let partial_b = b.partial_diffie_hellman(&their_public).unwrap();
+        let partial_b = PartialSharedSecret::from_bytes(partial_b.to_bytes()).unwrap();
+
+        let shared = combine(&partial_a, &partial_b).unwrap();
+        let expected = secret.diffie_hellman(&their_public);
+
+        assert_eq!(shared.to_bytes(), expected.to_bytes());
+        assert_eq!(shared.to_bytes(), their_secret.diffie_hellman(&PublicKey::from(&secret)).to_bytes());
+    }
+
+    #[test]
+    fn threshold_rejects_invalid_points() {
+        let csprng: OsRng = OsRng;
+
+        let (a, b) = split(&StaticSecret::new(csprng), csprng);
+        let their_public = PublicKey::from(&StaticSecret::new(csprng));
+
+        let mut off_curve = their_public.to_bytes();
+        off_curve[32] ^= 1;
+        assert!(a.partial_diffie_hellman(&PublicKey::from(off_curve)).is_none());
+        assert!(a.partial_diffie_hellman(&PublicKey::from([0u8; 64])).is_none());
+        assert!(PartialSharedSecret::from_bytes(off_curve).is_none());
+        assert!(PartialSharedSecret::from_bytes([0u8; 64]).is_none());
+
+        // The image of the Montgomery point (0, 0) of order 2
+        let torsion = PublicKey(WeierstrassPoint::from_montgomery([0u8; 32], [0u8; 32]));
+        assert!(torsion.0.is_on_curve());
+        assert!(a.partial_diffie_hellman(&torsion).is_none());
+
+        // Contributions which cancel each other out are rejected.
+        let partial_a = a.partial_diffie_hellman(&their_public).unwrap();
+        let partial_b = b.partial_diffie_hellman(&their_public).unwrap();
+        let negated = PartialSharedSecret(partial_a.0 * -Scalar::one());
+        assert!(combine(&partial_a, &negated).is_none());
+        assert!(combine(&partial_a, &partial_b).is_some());
+    }
+}
@@ -1,12 +1,22 @@
+use core::convert::TryFrom;
+
 use curve25519_dalek::constants::WEI25519_BASEPOINT;
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use curve25519_dalek::montgomery::MontgomeryPoint;
 use curve25519_dalek::weierstrass::{WeierstrassPoint, X25519_BASEPOINT_U, X25519_BASEPOINT_V};
 use curve25519_dalek::scalar::Scalar;
 use x25519_dalek::{clamp_scalar, SharedSecret};
 
+#[cfg(feature = "sec1")]
+use curve25519_dalek::sec1::{consts::U32, EncodedPoint};
+
+use hkdf::Hkdf;
+use sha2::Sha256;
+
 use rand_core::CryptoRng;
 use rand_core::RngCore;
 
-use zeroize::Zeroize;
+use zeroize::{Zeroize, Zeroizing};
 
 /// A Diffie-Hellman public key, corresponding to an [`EphemeralSecret`] or
 /// [`StaticSecret`] key.
@@ -15,33 +25,305 @@ use zeroize::Zeroize;
 /// should they wish to erase public keys from memory.  Note that this erasure
 /// (in this crate) does *not* automatically happen, but either must be derived
 /// for Drop or explicitly called.
-#[cfg_attr(feature = "serde", serde(crate = "our_serde"))]
 #[cfg_attr(
     feature = "serde",
     derive(our_serde::Serialize, our_serde::Deserialize)
 )]
+#[cfg_attr(feature = "serde", serde(crate = "our_serde"))]
 #[derive(PartialEq, Eq, Hash, Copy, Clone, Debug, Zeroize)]
 pub struct PublicKey(pub(crate) WeierstrassPoint);
 
 impl From<[u8; 64]> for PublicKey {
     /// Given a byte array, construct a w25519 `PublicKey`.
     fn from(bytes: [u8; 64]) -> PublicKey {
-        PublicKey(WeierstrassPoint::from(bytes))
+        PublicKey::from_bytes(&bytes)
     }
 }
 
 impl PublicKey {
+    /// Construct a w25519 `PublicKey` from the byte array of [`PublicKey::to_bytes`].
+    ///
+    /// This is a `const fn`, so peer keys can be baked into firmware at compile time.  Like
+    /// `PublicKey::from`, it does not validate the key: only embed keys which were validated
+    /// when they were provisioned, e.g. with [`PublicKey::parse_auto`].
+    pub const fn from_bytes(bytes: &[u8; 64]) -> PublicKey {
+        PublicKey(WeierstrassPoint::from_bytes(bytes))
+    }
+
     /// Convert this public key to a byte array.
     #[inline]
-    pub fn to_bytes(&self) -> [u8; 64] {
+    pub const fn to_bytes(&self) -> [u8; 64] {
         self.0.to_bytes()
     }
 
+    /// Compress this public key to 33 bytes in the SEC1 compressed format, i.e. a `0x02`
+    /// (even y) or `0x03` (odd y) tag followed by the big-endian x-coordinate.
+    pub fn compress(&self) -> [u8; 33] {
+        let p = self.0.canonical();
+
+        let mut bytes = [0u8; 33];
+        bytes[0] = 0x02 | p.y_is_odd() as u8;
+        bytes[1..].copy_from_slice(&p.x);
+        bytes[1..].reverse();
+        bytes
+    }
+
+    /// Check whether the y-coordinate of this public key is odd.
+    pub fn y_is_odd(&self) -> bool {
+        self.0.y_is_odd()
+    }
+
+    /// Drop this public key to its [`XOnlyPublicKey`], returning the parity of the
+    /// y-coordinate alongside.
+    ///
+    /// `XOnlyPublicKey::public_key` with the returned parity recovers this public key.
+    pub fn x_only_public_key(&self) -> (XOnlyPublicKey, bool) {
+        let compressed = self.compress();
+
+        let mut x = [0u8; 32];
+        x.copy_from_slice(&compressed[1..]);
+        (XOnlyPublicKey(x), compressed[0] == 0x03)
+    }
+
+    /// Decompress a public key from the SEC1 compressed format, see [`PublicKey::compress`].
+    ///
+    /// Returns `None` if the tag is invalid, the x-coordinate is not canonical or there is no
+    /// point on Wei25519 with this x-coordinate.
+    pub fn decompress(bytes: &[u8; 33]) -> Option<PublicKey> {
+        let y_is_odd = match bytes[0] {
+            0x02 => false,
+            0x03 => true,
+            _ => return None,
+        };
+
+        let mut x = [0u8; 32];
+        x.copy_from_slice(&bytes[1..]);
+        x.reverse();
+
+        WeierstrassPoint::decompress(&x, y_is_odd).map(PublicKey)
+    }
+
+    /// Parse a peer's public key, detecting its encoding from the length of `bytes`:
+    ///
+    /// * 32 bytes: an x25519 public key, i.e. a Montgomery u-coordinate,
+    /// * 33 bytes: the SEC1 compressed format, see [`PublicKey::decompress`],
+    /// * 64 bytes: the raw format of [`PublicKey::to_bytes`],
+    /// * 65 bytes: the SEC1 uncompressed format, i.e. a `0x04` tag followed by the big-endian
+    ///   x- and y-coordinates.
+    ///
+    /// An x25519 public key determines a point only up to sign, so it is normalized to the
+    /// point with an even y-coordinate.  Both points yield the same shared secret, but keys
+    /// parsed from x25519 public keys do not in general compare equal to the sender's w25519
+    /// key; compare them with [`PublicKey::corresponds_to_x25519`] instead.
+    ///
+    /// Returns `None` if the length matches none of these encodings, the encoding is not
+    /// canonical, or it is not a point on Wei25519 other than the point at infinity.
+    pub fn parse_auto(bytes: &[u8]) -> Option<PublicKey> {
+        match bytes.len() {
+            32 => {
+                let mut u = [0u8; 32];
+                u.copy_from_slice(bytes);
+
+                let edwards = MontgomeryPoint(u).to_edwards(0)?;
+                let point = WeierstrassPoint::from_edwards(&edwards).canonical();
+                // u = 0 maps to the point at infinity, non-canonical u does not roundtrip.
+                if point == WeierstrassPoint::default() || point.into_montgomery_compressed().0 != u {
+                    return None;
+                }
+
+                WeierstrassPoint::decompress(&point.x, false).map(PublicKey)
+            }
+            33 => PublicKey::decompress(<&[u8; 33]>::try_from(bytes).ok()?),
+            64 => {
+                let mut x = [0u8; 32];
+                let mut y = [0u8; 32];
+                x.copy_from_slice(&bytes[..32]);
+                y.copy_from_slice(&bytes[32..]);
+                PublicKey::from_coordinates(x, y)
+            }
+            65 if bytes[0] == 0x04 => {
+                let mut x = [0u8; 32];
+                let mut y = [0u8; 32];
+                x.copy_from_slice(&bytes[1..33]);
+                y.copy_from_slice(&bytes[33..]);
+                x.reverse();
+                y.reverse();
+                PublicKey::from_coordinates(x, y)
+            }
+            _ => None,
+        }
+    }
+
+    /// Check that this public key is a point on Wei25519 other than the point at infinity,
+    /// i.e. the partial public-key validation of SP 800-56A.
+    pub(crate) fn is_valid(&self) -> bool {
+        self.0.is_on_curve() && self.0 != WeierstrassPoint::default()
+    }
+
+    /// Validate little-endian affine coordinates, accepting only canonical encodings of
+    /// points on Wei25519 other than the point at infinity.
+    pub(crate) fn from_coordinates(x: [u8; 32], y: [u8; 32]) -> Option<PublicKey> {
+        let point = WeierstrassPoint { x, y };
+        let canonical = point.canonical();
+
+        if canonical.x != x
+            || canonical.y != y
+            || !point.is_on_curve()
+            || (x == [0u8; 32] && y == [0u8; 32])
+        {
+            return None;
+        }
+        Some(PublicKey(point))
+    }
+
+    /// Convert an ed25519 public key, i.e. a compressed edwards25519 point, to the w25519
+    /// `PublicKey` of the same secret scalar.
+    ///
+    /// Returns `None` if the bytes are not a valid point or map to the point at infinity.
+    pub fn from_ed25519_public_key(bytes: &[u8; 32]) -> Option<PublicKey> {
+        let edwards = CompressedEdwardsY(*bytes).decompress()?;
+        let point = WeierstrassPoint::from_edwards(&edwards);
+
+        if point == WeierstrassPoint::default() {
+            None
+        } else {
+            Some(PublicKey(point))
+        }
+    }
+
+    /// Check whether this public key belongs to the same secret scalar as the ed25519 public
+    /// key `ed25519`.
+    ///
+    /// Wei25519 and edwards25519 are isomorphic groups with corresponding base points, so the
+    /// equivalence of the keys is a public, deterministic check and needs no proof of
+    /// knowledge.
+    pub fn corresponds_to_ed25519(&self, ed25519: &[u8; 32]) -> bool {
+        PublicKey::from_ed25519_public_key(ed25519) == Some(*self)
+    }
+
+    /// Check whether this public key belongs to the same secret scalar as the x25519 public
+    /// key `x25519`, up to sign.
+    ///
+    /// An x25519 public key only carries the Montgomery u-coordinate, so it corresponds to
+    /// both this key and its negation, i.e. the secret scalars `s` and `-s`.
+    pub fn corresponds_to_x25519(&self, x25519: &x25519_dalek::PublicKey) -> bool {
+        self.to_x25519_public_key().as_bytes() == x25519.as_bytes()
+    }
+
+    /// Tweak this public key additively, computing `P + t·G`.
+    ///
+    /// This is the public key of [`StaticSecret::add_tweak`] and [`DerivedSecret::add_tweak`]
+    /// with the same `tweak`.
+    ///
+    /// Returns `None` if the result is the point at infinity.
+    pub fn add_tweak(&self, tweak: &Scalar) -> Option<PublicKey> {
+        non_identity(self.0 + WEI25519_BASEPOINT * tweak).map(PublicKey)
+    }
+
+    /// Tweak this public key multiplicatively, computing `t·P`.
+    ///
+    /// This is the public key of [`StaticSecret::mul_tweak`] and [`DerivedSecret::mul_tweak`]
+    /// with the same `tweak`.
+    ///
+    /// Returns `None` if the result is the point at infinity.
+    pub fn mul_tweak(&self, tweak: &Scalar) -> Option<PublicKey> {
+        non_identity(self.0 * tweak).map(PublicKey)
+    }
+
+    /// Convert this public key to an x25519 [`x25519_dalek::PublicKey`] by dropping it to its
+    /// Montgomery u-coordinate, for peers which only speak RFC7748.
     pub fn to_x25519_public_key(&self) -> x25519_dalek::PublicKey {
         x25519_dalek::PublicKey::from(self.0.into_montgomery_compressed().0)
     }
 }
 
+/// A public key given by its x-coordinate only, e.g. for BIP340-style protocols or compact
+/// storage.
+///
+/// An x-coordinate determines a point up to sign.  By convention an `XOnlyPublicKey` stands
+/// for the point with an even y-coordinate, so converting it to a [`PublicKey`] yields that
+/// point unless the parity is given explicitly.
+#[cfg_attr(
+    feature = "serde",
+    derive(our_serde::Serialize, our_serde::Deserialize)
+)]
+#[cfg_attr(feature = "serde", serde(crate = "our_serde"))]
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug, Zeroize)]
+pub struct XOnlyPublicKey([u8; 32]);
+
+impl XOnlyPublicKey {
+    /// Load an x-only public key from its 32-byte big-endian x-coordinate.
+    ///
+    /// Returns `None` if the x-coordinate is not canonical or there is no point on Wei25519
+    /// with this x-coordinate.
+    pub fn from_bytes(bytes: &[u8; 32]) -> Option<XOnlyPublicKey> {
+        let key = XOnlyPublicKey(*bytes);
+        key.public_key(false)?;
+        Some(key)
+    }
+
+    /// Convert this x-only public key to its 32-byte big-endian x-coordinate.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+
+    /// Lift this x-only public key to the [`PublicKey`] with the given parity of the
+    /// y-coordinate.
+    ///
+    /// Returns `None` if there is no such point, i.e. if the y-coordinate is zero and
+    /// `y_is_odd` is set.
+    pub fn public_key(&self, y_is_odd: bool) -> Option<PublicKey> {
+        let mut compressed = [0u8; 33];
+        compressed[0] = 0x02 | y_is_odd as u8;
+        compressed[1..].copy_from_slice(&self.0);
+
+        PublicKey::decompress(&compressed)
+    }
+
+    /// Lift this x-only public key to the [`PublicKey`] with an even y-coordinate.
+    ///
+    /// Returns `None` if there is no point with this x-coordinate, which happens if the key was
+    /// dropped from a `PublicKey` which is not on Wei25519, e.g. one loaded unchecked with
+    /// `PublicKey::from`.
+    pub fn to_public_key(&self) -> Option<PublicKey> {
+        self.public_key(false)
+    }
+}
+
+impl From<PublicKey> for XOnlyPublicKey {
+    /// Drop a [`PublicKey`] to its x-coordinate, see [`PublicKey::x_only_public_key`].
+    fn from(public: PublicKey) -> XOnlyPublicKey {
+        public.x_only_public_key().0
+    }
+}
+
+#[cfg(feature = "sec1")]
+impl PublicKey {
+    /// Encode this public key in the SEC1 `Elliptic-Curve-Point-to-Octet-String` format.
+    pub fn to_encoded_point(&self, compress: bool) -> EncodedPoint<U32> {
+        self.0.to_encoded_point(compress)
+    }
+
+    /// Decode a public key from the SEC1 `Octet-String-to-Elliptic-Curve-Point` format.
+    ///
+    /// Returns `None` if the encoding is not canonical, is not a point on Wei25519 or is the
+    /// point at infinity.
+    pub fn from_encoded_point(encoded: &EncodedPoint<U32>) -> Option<PublicKey> {
+        if encoded.is_identity() {
+            return None;
+        }
+
+        WeierstrassPoint::from_encoded_point(encoded).map(PublicKey)
+    }
+}
+
+impl From<PublicKey> for x25519_dalek::PublicKey {
+    /// Given a w25519 [`PublicKey`], construct the corresponding x25519 `PublicKey`.
+    fn from(public: PublicKey) -> x25519_dalek::PublicKey {
+        public.to_x25519_public_key()
+    }
+}
+
 /// A short-lived Diffie-Hellman secret key that can only be used to compute a single
 /// [`SharedSecret`].
 ///
@@ -57,8 +339,25 @@ pub struct EphemeralSecret(pub(crate) Scalar);
 impl EphemeralSecret {
     /// Perform a Diffie-Hellman key agreement between `self` and
     /// `their_public` key to produce a [`SharedSecret`].
+    #[allow(clippy::op_ref)] // borrow the secret scalar instead of copying it
     pub fn diffie_hellman(self, their_public: &PublicKey) -> SharedSecret {
-        SharedSecret::new((self.0 * their_public.0).into_montgomery_compressed())
+        let guard = NoPanic;
+        let shared = SharedSecret::new((&self.0 * their_public.0).into_montgomery_compressed());
+        guard.disarm();
+        shared
+    }
+
+    /// Perform a Diffie-Hellman key agreement like [`EphemeralSecret::diffie_hellman`], but
+    /// verify `their_public` and the resulting point before returning it.
+    ///
+    /// Returns `None` if a check fails, see [`StaticSecret::diffie_hellman_checked`].
+    #[allow(clippy::op_ref)] // borrow the secret scalar instead of copying it
+    pub fn diffie_hellman_checked(self, their_public: &PublicKey) -> Option<SharedSecret> {
+        let guard = NoPanic;
+        let shared = checked(&their_public.0, |p| &self.0 * p)
+            .map(|p| SharedSecret::new(p.into_montgomery_compressed()));
+        guard.disarm();
+        shared
     }
 
     /// Generate an w25519 [`EphemeralSecret`] key.
@@ -69,6 +368,28 @@ impl EphemeralSecret {
 
         EphemeralSecret(clamp_scalar(bytes))
     }
+
+    /// Generate an w25519 [`EphemeralSecret`] key with an RNG of any supported `rand_core` version, see
+    /// [`KeyRng`](crate::rng::KeyRng).
+    #[cfg(any(feature = "rand_core_06", feature = "rand_core_09"))]
+    pub fn from_rng<M, T: crate::rng::KeyRng<M>>(mut csprng: T) -> Self {
+        let mut bytes = [0u8; 32];
+        csprng.fill_key_bytes(&mut bytes);
+
+        let secret = EphemeralSecret(clamp_scalar(bytes));
+        bytes.zeroize();
+        secret
+    }
+
+    /// Construct an [`EphemeralSecret`] from fixed bytes, clamping them like
+    /// [`StaticSecret::from`].
+    ///
+    /// This is only meant to run test vectors with fixed ephemeral keys against this API and
+    /// defeats the purpose of an ephemeral secret otherwise.
+    #[cfg(feature = "insecure_test_vectors")]
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        EphemeralSecret(clamp_scalar(bytes))
+    }
 }
 
 impl<'a> From<&'a EphemeralSecret> for PublicKey {
@@ -105,8 +426,25 @@ pub struct ReusableSecret(pub(crate) Scalar);
 impl ReusableSecret {
     /// Perform a Diffie-Hellman key agreement between `self` and
     /// `their_public` key to produce a [`SharedSecret`].
+    #[allow(clippy::op_ref)] // borrow the secret scalar instead of copying it
     pub fn diffie_hellman(&self, their_public: &PublicKey) -> SharedSecret {
-        SharedSecret::new((&self.0 * their_public.0).into_montgomery_compressed())
+        let guard = NoPanic;
+        let shared = SharedSecret::new((&self.0 * their_public.0).into_montgomery_compressed());
+        guard.disarm();
+        shared
+    }
+
+    /// Perform a Diffie-Hellman key agreement like [`ReusableSecret::diffie_hellman`], but
+    /// verify `their_public` and the resulting point before returning it.
+    ///
+    /// Returns `None` if a check fails, see [`StaticSecret::diffie_hellman_checked`].
+    #[allow(clippy::op_ref)] // borrow the secret scalar instead of copying it
+    pub fn diffie_hellman_checked(&self, their_public: &PublicKey) -> Option<SharedSecret> {
+        let guard = NoPanic;
+        let shared = checked(&their_public.0, |p| &self.0 * p)
+            .map(|p| SharedSecret::new(p.into_montgomery_compressed()));
+        guard.disarm();
+        shared
     }
 
     /// Generate a non-serializeable x25519 [`ReuseableSecret`] key.
@@ -117,6 +455,28 @@ impl ReusableSecret {
 
         ReusableSecret(clamp_scalar(bytes))
     }
+
+    /// Generate a [`ReusableSecret`] key with an RNG of any supported `rand_core` version, see
+    /// [`KeyRng`](crate::rng::KeyRng).
+    #[cfg(any(feature = "rand_core_06", feature = "rand_core_09"))]
+    pub fn from_rng<M, T: crate::rng::KeyRng<M>>(mut csprng: T) -> Self {
+        let mut bytes = [0u8; 32];
+        csprng.fill_key_bytes(&mut bytes);
+
+        let secret = ReusableSecret(clamp_scalar(bytes));
+        bytes.zeroize();
+        secret
+    }
+
+    /// Construct a [`ReusableSecret`] from fixed bytes, clamping them like
+    /// [`StaticSecret::from`].
+    ///
+    /// This is only meant to run test vectors with fixed keys against this API, see
+    /// [`EphemeralSecret::from_bytes`].
+    #[cfg(feature = "insecure_test_vectors")]
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        ReusableSecret(clamp_scalar(bytes))
+    }
 }
 
 #[cfg(feature = "reusable_secrets")]
@@ -141,11 +501,11 @@ impl<'a> From<&'a ReusableSecret> for PublicKey {
 /// [`EphemeralSecret`] at all times, as that type enforces at compile-time that
 /// secret keys are never reused, which can have very serious security
 /// implications for many protocols.
-#[cfg_attr(feature = "serde", serde(crate = "our_serde"))]
 #[cfg_attr(
     feature = "serde",
     derive(our_serde::Serialize, our_serde::Deserialize)
 )]
+#[cfg_attr(feature = "serde", serde(crate = "our_serde"))]
 #[derive(Clone, Zeroize)]
 #[zeroize(drop)]
 pub struct StaticSecret(
@@ -155,8 +515,34 @@ pub struct StaticSecret(
 impl StaticSecret {
     /// Perform a Diffie-Hellman key agreement between `self` and
     /// `their_public` key to produce a `SharedSecret`.
+    #[allow(clippy::op_ref)] // borrow the secret scalar instead of copying it
     pub fn diffie_hellman(&self, their_public: &PublicKey) -> SharedSecret {
-        SharedSecret::new((&self.0 * their_public.0).into_montgomery_compressed())
+        let guard = NoPanic;
+        let shared = SharedSecret::new((&self.0 * their_public.0).into_montgomery_compressed());
+        guard.disarm();
+        shared
+    }
+
+    /// Perform a Diffie-Hellman key agreement like [`StaticSecret::diffie_hellman`], but
+    /// verify that `their_public` and the resulting point lie on Wei25519 before returning it.
+    ///
+    /// A fault injected into the scalar multiplication almost certainly produces a point
+    /// which is not on the curve, and releasing such a faulty shared secret can leak bits of
+    /// the secret key.  Checking `their_public` rejects invalid-curve points, and rejecting
+    /// the point at infinity catches points of small order, including those on a twist which
+    /// the multiplication sends to infinity.
+    ///
+    /// # Returns
+    ///
+    /// `None` if `their_public` is not on Wei25519, or if the resulting point is not on
+    /// Wei25519 or is the point at infinity.
+    #[allow(clippy::op_ref)] // borrow the secret scalar instead of copying it
+    pub fn diffie_hellman_checked(&self, their_public: &PublicKey) -> Option<SharedSecret> {
+        let guard = NoPanic;
+        let shared = checked(&their_public.0, |p| &self.0 * p)
+            .map(|p| SharedSecret::new(p.into_montgomery_compressed()));
+        guard.disarm();
+        shared
     }
 
     /// Generate an w25519 key.
@@ -168,11 +554,84 @@ impl StaticSecret {
         StaticSecret(clamp_scalar(bytes))
     }
 
+    /// Generate an w25519 key with an RNG of any supported `rand_core` version, see
+    /// [`KeyRng`](crate::rng::KeyRng).
+    #[cfg(any(feature = "rand_core_06", feature = "rand_core_09"))]
+    pub fn from_rng<M, T: crate::rng::KeyRng<M>>(mut csprng: T) -> Self {
+        let mut bytes = [0u8; 32];
+        csprng.fill_key_bytes(&mut bytes);
+
+        let secret = StaticSecret(clamp_scalar(bytes));
+        bytes.zeroize();
+        secret
+    }
+
+    /// Deterministically derive a w25519 key from a device-unique `seed`, separated by
+    /// `domain`.
+    ///
+    /// The key is derived as
+    ///
+    /// ```text
+    /// k = HKDF-SHA-256(salt = "w25519 StaticSecret::from_seed", IKM = seed, info = domain)
+    /// ```
+    ///
+    /// with a 32-byte output which is then clamped.  Different `domain`s yield independent
+    /// keys from the same seed.
+    ///
+    /// # Warning
+    ///
+    /// The derivation does not add any entropy: `seed` must be a uniformly random secret of at
+    /// least 32 bytes, not a password or other low-entropy value.
+    pub fn from_seed(seed: &[u8], domain: &[u8]) -> Self {
+        let mut bytes = [0u8; 32];
+        Hkdf::<Sha256>::new(Some(b"w25519 StaticSecret::from_seed"), seed)
+            .expand(domain, &mut bytes)
+            .expect("32 bytes is a valid HKDF-SHA-256 output length");
+
+        let secret = StaticSecret(clamp_scalar(bytes));
+        bytes.zeroize();
+        secret
+    }
+
     /// Extract this key's bytes for serialization.
     pub fn to_bytes(&self) -> [u8; 32] {
         self.0.to_bytes()
     }
 
+    /// Extract this key's bytes for serialization, wiping them when they are dropped.
+    pub fn to_bytes_zeroizing(&self) -> Zeroizing<[u8; 32]> {
+        Zeroizing::new(self.to_bytes())
+    }
+
+    /// Derive a child key by adding `tweak`, i.e. the secret `s + t` whose public key is
+    /// [`PublicKey::add_tweak`] of this key's public key.
+    ///
+    /// The sum is taken modulo the group order.  It is generally not a clamped scalar, so the
+    /// child key is a [`DerivedSecret`] rather than a `StaticSecret`.
+    ///
+    /// Returns `None` if the child key is zero.
+    #[allow(clippy::op_ref)] // borrow the secret scalar instead of copying it
+    pub fn add_tweak(&self, tweak: &Scalar) -> Option<DerivedSecret> {
+        let mut s = self.0.reduce();
+        let child = DerivedSecret(&s + tweak);
+        s.zeroize();
+        child.non_zero()
+    }
+
+    /// Derive a child key by multiplying with `tweak`, i.e. the secret `s·t` whose public key
+    /// is [`PublicKey::mul_tweak`] of this key's public key.
+    ///
+    /// See [`StaticSecret::add_tweak`] for the representation of the child key.
+    ///
+    /// Returns `None` if the child key is zero, i.e. if `tweak` is zero.
+    #[allow(clippy::op_ref)] // borrow the secret scalar instead of copying it
+    pub fn mul_tweak(&self, tweak: &Scalar) -> Option<DerivedSecret> {
+        let mut s = self.0.reduce();
+        let child = DerivedSecret(&s * tweak);
+        s.zeroize();
+        child.non_zero()
+    }
+
     pub fn to_x25519_static_secret(&self) -> x25519_dalek::StaticSecret {
         x25519_dalek::StaticSecret::from(self.to_bytes())
     }
@@ -185,6 +644,29 @@ impl From<[u8; 32]> for StaticSecret {
     }
 }
 
+impl From<x25519_dalek::StaticSecret> for StaticSecret {
+    /// Convert an x25519 [`x25519_dalek::StaticSecret`] into a w25519 [`StaticSecret`].
+    ///
+    /// Both are clamped Curve25519 scalars, so the resulting key computes the same
+    /// shared secrets.
+    fn from(secret: x25519_dalek::StaticSecret) -> StaticSecret {
+        let mut bytes = secret.to_bytes();
+        let result = StaticSecret::from(bytes);
+        bytes.zeroize();
+        result
+    }
+}
+
+impl From<StaticSecret> for x25519_dalek::StaticSecret {
+    /// Convert a w25519 [`StaticSecret`] into an x25519 [`x25519_dalek::StaticSecret`].
+    fn from(secret: StaticSecret) -> x25519_dalek::StaticSecret {
+        let mut bytes = secret.to_bytes();
+        let result = x25519_dalek::StaticSecret::from(bytes);
+        bytes.zeroize();
+        result
+    }
+}
+
 impl<'a> From<&'a StaticSecret> for PublicKey {
     /// Given an w25519 [`StaticSecret`] key, compute its corresponding [`PublicKey`].
     fn from(secret: &'a StaticSecret) -> PublicKey {
@@ -192,11 +674,198 @@ impl<'a> From<&'a StaticSecret> for PublicKey {
     }
 }
 
+/// A Diffie-Hellman secret key derived from other key material, e.g. from an implicit
+/// certificate with [`crate::ecqv::RequestSecret::derive_private_key`].
+///
+/// A derived key is an arbitrary scalar rather than a clamped one, so it can not be a
+/// [`StaticSecret`] without being changed by the clamping on the next load.  It is kept
+/// reduced modulo the group order instead, which [`DerivedSecret::to_bytes`] and
+/// [`DerivedSecret::from_bytes`] round-trip exactly.
+///
+/// Diffie-Hellman multiplies the peer's key by the multiple of the cofactor 8 which is
+/// congruent to the key, so small-order components of peer keys are cleared as with a clamped
+/// secret.
+#[cfg_attr(
+    feature = "serde",
+    derive(our_serde::Serialize, our_serde::Deserialize)
+)]
+#[cfg_attr(feature = "serde", serde(crate = "our_serde"))]
+#[derive(Clone, Zeroize)]
+#[zeroize(drop)]
+pub struct DerivedSecret(pub(crate) Scalar);
+
+impl DerivedSecret {
+    /// Perform a Diffie-Hellman key agreement between `self` and
+    /// `their_public` key to produce a `SharedSecret`.
+    pub fn diffie_hellman(&self, their_public: &PublicKey) -> SharedSecret {
+        let guard = NoPanic;
+        let shared = SharedSecret::new(self.multiply(&their_public.0).into_montgomery_compressed());
+        guard.disarm();
+        shared
+    }
+
+    /// Perform a Diffie-Hellman key agreement like [`DerivedSecret::diffie_hellman`], but
+    /// verify `their_public` and the resulting point before returning it.
+    ///
+    /// Returns `None` if a check fails, see [`StaticSecret::diffie_hellman_checked`].
+    pub fn diffie_hellman_checked(&self, their_public: &PublicKey) -> Option<SharedSecret> {
+        let guard = NoPanic;
+        let shared = checked(&their_public.0, |p| self.multiply(p))
+            .map(|p| SharedSecret::new(p.into_montgomery_compressed()));
+        guard.disarm();
+        shared
+    }
+
+    /// Extract this key's bytes for serialization, i.e. the canonical encoding of the scalar.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_bytes()
+    }
+
+    /// Extract this key's bytes for serialization, wiping them when they are dropped.
+    pub fn to_bytes_zeroizing(&self) -> Zeroizing<[u8; 32]> {
+        Zeroizing::new(self.to_bytes())
+    }
+
+    /// Load a derived key from the bytes of [`DerivedSecret::to_bytes`].
+    ///
+    /// Returns `None` if the bytes are not a canonical scalar or the key is zero.
+    pub fn from_bytes(bytes: [u8; 32]) -> Option<DerivedSecret> {
+        DerivedSecret(Scalar::from_canonical_bytes(bytes)?).non_zero()
+    }
+
+    /// Derive a child key by adding `tweak`, i.e. the secret `d + t` whose public key is
+    /// [`PublicKey::add_tweak`] of this key's public key, e.g. for the next level of a key
+    /// hierarchy started with [`StaticSecret::add_tweak`].
+    ///
+    /// Returns `None` if the child key is zero.
+    #[allow(clippy::op_ref)] // borrow the secret scalar instead of copying it
+    pub fn add_tweak(&self, tweak: &Scalar) -> Option<DerivedSecret> {
+        DerivedSecret(&self.0 + tweak).non_zero()
+    }
+
+    /// Derive a child key by multiplying with `tweak`, i.e. the secret `d·t` whose public key
+    /// is [`PublicKey::mul_tweak`] of this key's public key.
+    ///
+    /// Returns `None` if the child key is zero, i.e. if `tweak` is zero.
+    #[allow(clippy::op_ref)] // borrow the secret scalar instead of copying it
+    pub fn mul_tweak(&self, tweak: &Scalar) -> Option<DerivedSecret> {
+        DerivedSecret(&self.0 * tweak).non_zero()
+    }
+
+    /// Reject the zero key, whose public key is the point at infinity.
+    pub(crate) fn non_zero(self) -> Option<DerivedSecret> {
+        if self.0 == Scalar::zero() {
+            None
+        } else {
+            Some(self)
+        }
+    }
+
+    /// Compute `8·(k/8)·P`, i.e. `k·P` with any small-order component of `P` cleared.
+    #[allow(clippy::op_ref)] // borrow the secret scalar instead of copying it
+    fn multiply(&self, point: &WeierstrassPoint) -> WeierstrassPoint {
+        let mut t = self.0 * Scalar::from(8u64).invert();
+        let mut p = point * &t;
+        t.zeroize();
+
+        p.double_in_place();
+        p.double_in_place();
+        p.double_in_place();
+        p
+    }
+}
+
+impl<'a> From<&'a DerivedSecret> for PublicKey {
+    /// Given a [`DerivedSecret`] key, compute its corresponding [`PublicKey`].
+    #[allow(clippy::op_ref)] // borrow the secret scalar instead of copying it
+    fn from(secret: &'a DerivedSecret) -> PublicKey {
+        PublicKey(WEI25519_BASEPOINT * &secret.0)
+    }
+}
+
+/// A Diffie-Hellman secret key, to write protocols generically over [`StaticSecret`],
+/// [`ReusableSecret`], [`EphemeralSecret`] and [`DerivedSecret`].
+///
+/// The trait is implemented for references to the reusable secret types and for
+/// [`EphemeralSecret`] by value, so generic code still uses an ephemeral secret at most once:
+///
+/// ```
+/// use w25519::{DiffieHellman, PublicKey};
+///
+/// fn agree<S: DiffieHellman>(secret: S, their_public: &PublicKey) -> (PublicKey, [u8; 32]) {
+///     (secret.public(), secret.dh(their_public).to_bytes())
+/// }
+/// ```
+pub trait DiffieHellman {
+    /// Compute the [`PublicKey`] corresponding to this secret.
+    fn public(&self) -> PublicKey;
+
+    /// Perform a Diffie-Hellman key agreement with `their_public`, see
+    /// [`StaticSecret::diffie_hellman`].
+    fn dh(self, their_public: &PublicKey) -> SharedSecret;
+}
+
+impl DiffieHellman for EphemeralSecret {
+    fn public(&self) -> PublicKey {
+        PublicKey::from(self)
+    }
+
+    fn dh(self, their_public: &PublicKey) -> SharedSecret {
+        self.diffie_hellman(their_public)
+    }
+}
+
+#[cfg(feature = "reusable_secrets")]
+impl DiffieHellman for &ReusableSecret {
+    fn public(&self) -> PublicKey {
+        PublicKey::from(*self)
+    }
+
+    fn dh(self, their_public: &PublicKey) -> SharedSecret {
+        self.diffie_hellman(their_public)
+    }
+}
+
+impl DiffieHellman for &StaticSecret {
+    fn public(&self) -> PublicKey {
+        PublicKey::from(*self)
+    }
+
+    fn dh(self, their_public: &PublicKey) -> SharedSecret {
+        self.diffie_hellman(their_public)
+    }
+}
+
+impl DiffieHellman for &DerivedSecret {
+    fn public(&self) -> PublicKey {
+        PublicKey::from(*self)
+    }
+
+    fn dh(self, their_public: &PublicKey) -> SharedSecret {
+        self.diffie_hellman(their_public)
+    }
+}
+
 /// The bare, byte-oriented w25519 function, interoperable with RFC7748 by only using the u-coordinates.
 /// 
 /// This can be used with [`W25519_BASEPOINT_BYTES_U`], [`W25519_BASEPOINT_BYTES_V`] (or [`w25519_base_point`]).
 pub fn w25519(k: [u8; 32], u: [u8; 32], v: [u8; 32]) -> ([u8; 32], [u8; 32]) {
-    (clamp_scalar(k) * WeierstrassPoint::from_montgomery(u, v)).into_montgomery()
+    let guard = NoPanic;
+    let result = (clamp_scalar(k) * WeierstrassPoint::from_montgomery(u, v)).into_montgomery();
+    guard.disarm();
+    result
+}
+
+/// The bare, byte-oriented w25519 function which verifies the input and the result like
+/// [`StaticSecret::diffie_hellman_checked`].
+///
+/// Returns `None` if a check fails.
+pub fn w25519_checked(k: [u8; 32], u: [u8; 32], v: [u8; 32]) -> Option<([u8; 32], [u8; 32])> {
+    let guard = NoPanic;
+    let point = WeierstrassPoint::from_montgomery(u, v);
+    let result = checked(&point, |p| clamp_scalar(k) * p).map(|p| p.into_montgomery());
+    guard.disarm();
+    result
 }
 
 pub fn w25519_base_point(k: [u8; 32]) -> ([u8; 32], [u8; 32]) {
@@ -206,6 +875,75 @@ pub fn w25519_base_point(k: [u8; 32]) -> ([u8; 32], [u8; 32]) {
 pub const W25519_BASEPOINT_BYTES_U: [u8; 32] = X25519_BASEPOINT_U;
 pub const W25519_BASEPOINT_BYTES_V: [u8; 32] = X25519_BASEPOINT_V;
 
+/// Derived serialization methods will not work on a StaticSecret because w25519 requires
+/// non-canonical scalars which are rejected by curve25519-dalek. Thus we provide a way to convert
+/// the bytes directly to a scalar using Serde's remote derive functionality.
+#[cfg_attr(
+    feature = "serde",
+    derive(our_serde::Serialize, our_serde::Deserialize)
+)]
+#[cfg_attr(feature = "serde", serde(crate = "our_serde"))]
+#[cfg_attr(feature = "serde", serde(remote = "Scalar"))]
+struct AllowUnreducedScalarBytes(
+    #[cfg_attr(feature = "serde", serde(getter = "Scalar::to_bytes"))] [u8; 32],
+);
+impl From<AllowUnreducedScalarBytes> for Scalar {
+    fn from(bytes: AllowUnreducedScalarBytes) -> Scalar {
+        clamp_scalar(bytes.0)
+    }
+}
+
+fn non_identity(point: WeierstrassPoint) -> Option<WeierstrassPoint> {
+    if point == WeierstrassPoint::default() {
+        None
+    } else {
+        Some(point)
+    }
+}
+
+/// A guard which, with the `no_panic` feature, fails to link unless the optimizer proves that
+/// the code between its creation and [`NoPanic::disarm`] never unwinds.
+struct NoPanic;
+
+impl NoPanic {
+    #[inline(always)]
+    fn disarm(self) {
+        #[cfg(feature = "no_panic")]
+        core::mem::forget(self);
+    }
+}
+
+#[cfg(feature = "no_panic")]
+impl Drop for NoPanic {
+    #[inline(always)]
+    fn drop(&mut self) {
+        extern "C" {
+            #[link_name = "\n\nERROR: a w25519 operation checked by the `no_panic` feature may panic\n\n"]
+            fn w25519_may_panic() -> !;
+        }
+        unsafe { w25519_may_panic() }
+    }
+}
+
+/// Multiply the peer's `input` point only if it lies on Wei25519, and release the result of the
+/// secret-dependent scalar multiplication only if it lies on Wei25519 and is not the point at
+/// infinity.
+fn checked<F>(input: &WeierstrassPoint, multiply: F) -> Option<WeierstrassPoint>
+where
+    F: FnOnce(&WeierstrassPoint) -> WeierstrassPoint,
+{
+    if !input.is_on_curve() {
+        return None;
+    }
+
+    let point = multiply(input);
+    if point.is_on_curve() {
+        non_identity(point)
+    } else {
+        None
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -239,6 +977,351 @@ mod test {
         );
     }
 
+    #[test]
+    fn from_public_key_for_x25519_public_key() {
+        let pubkey = PublicKey::from(&StaticSecret::new(OsRng));
+
+        assert_eq!(x25519_dalek::PublicKey::from(pubkey), pubkey.to_x25519_public_key());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_bincode_roundtrip() {
+        let csprng: OsRng = OsRng;
+
+        let secret = StaticSecret::new(csprng);
+        let public = PublicKey::from(&secret);
+
+        let encoded = bincode::serialize(&secret).unwrap();
+        let decoded: StaticSecret = bincode::deserialize(&encoded).unwrap();
+        assert_eq!(decoded.to_bytes(), secret.to_bytes());
+
+        let encoded = bincode::serialize(&public).unwrap();
+        let decoded: PublicKey = bincode::deserialize(&encoded).unwrap();
+        assert_eq!(encoded.len(), 64);
+        assert_eq!(decoded, public);
+    }
+
+    #[test]
+    #[cfg(feature = "sec1")]
+    fn sec1_public_key_roundtrip() {
+        let csprng: OsRng = OsRng;
+
+        let public = PublicKey::from(&StaticSecret::new(csprng));
+
+        let compressed = public.to_encoded_point(true);
+        let uncompressed = public.to_encoded_point(false);
+
+        assert_eq!(compressed.len(), 33);
+        assert_eq!(compressed.as_bytes(), &public.compress()[..]);
+        assert_eq!(uncompressed.len(), 65);
+        assert_eq!(PublicKey::from_encoded_point(&compressed), Some(public));
+        assert_eq!(PublicKey::from_encoded_point(&uncompressed), Some(public));
+        assert_eq!(PublicKey::from_encoded_point(&EncodedPoint::identity()), None);
+    }
+
+    #[test]
+    fn compress_decompress() {
+        let csprng: OsRng = OsRng;
+
+        let keys = [
+            PublicKey::from(&StaticSecret::new(csprng)),
+            PublicKey::from(&StaticSecret::new(csprng)),
+            PublicKey::from(&StaticSecret::new(csprng)),
+        ];
+        let compressed = [keys[0].compress(), keys[1].compress(), keys[2].compress()];
+
+        for (compressed, key) in compressed.iter().zip(keys.iter()) {
+            assert_eq!(PublicKey::decompress(compressed), Some(*key));
+        }
+
+        let mut invalid = compressed[0];
+        invalid[0] = 0x04;
+        assert_eq!(PublicKey::decompress(&invalid), None);
+    }
+
+    #[test]
+    fn static_secret_from_seed() {
+        let seed = [0x42u8; 32];
+
+        let a = StaticSecret::from_seed(&seed, b"device key");
+        let b = StaticSecret::from_seed(&seed, b"device key");
+        let c = StaticSecret::from_seed(&seed, b"other key");
+        let d = StaticSecret::from_seed(&[0x43u8; 32], b"device key");
+
+        assert_eq!(a.to_bytes(), b.to_bytes());
+        assert_ne!(a.to_bytes(), c.to_bytes());
+        assert_ne!(a.to_bytes(), d.to_bytes());
+        assert_eq!(StaticSecret::from(a.to_bytes()).to_bytes(), a.to_bytes());
+    }
+
+    #[test]
+    fn static_secret_x25519_conversion() {
+        let csprng: OsRng = OsRng;
+
+        let secret_x = x25519_dalek::StaticSecret::new(csprng);
+        let pubkey_x = x25519_dalek::PublicKey::from(&secret_x);
+        let bytes = secret_x.to_bytes();
+
+        let secret_w = StaticSecret::from(secret_x);
+        assert_eq!(PublicKey::from(&secret_w).to_x25519_public_key(), pubkey_x);
+
+        let secret_x = x25519_dalek::StaticSecret::from(secret_w);
+        assert_eq!(secret_x.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn diffie_hellman_trait() {
+        fn agree<S: DiffieHellman>(secret: S, their_public: &PublicKey) -> (PublicKey, [u8; 32]) {
+            (secret.public(), secret.dh(their_public).to_bytes())
+        }
+
+        let csprng: OsRng = OsRng;
+        let their_secret = StaticSecret::new(csprng);
+        let their_public = PublicKey::from(&their_secret);
+
+        let (public, shared) = agree(EphemeralSecret::new(csprng), &their_public);
+        assert_eq!(shared, their_secret.diffie_hellman(&public).to_bytes());
+
+        let secret = StaticSecret::new(csprng);
+        let (public, shared) = agree(&secret, &their_public);
+        assert_eq!(public, PublicKey::from(&secret));
+        assert_eq!(shared, secret.diffie_hellman(&their_public).to_bytes());
+    }
+
+    #[test]
+    #[cfg(feature = "insecure_test_vectors")]
+    fn ephemeral_secret_from_bytes() {
+        let alice = [0x42u8; 32];
+        let bob = [0x24u8; 32];
+        let shared = x25519(alice, x25519(bob, x25519_dalek::X25519_BASEPOINT_BYTES));
+
+        let bob_public = PublicKey::from(&StaticSecret::from(bob));
+        assert_eq!(EphemeralSecret::from_bytes(alice).diffie_hellman(&bob_public).to_bytes(), shared);
+        #[cfg(feature = "reusable_secrets")]
+        assert_eq!(ReusableSecret::from_bytes(alice).diffie_hellman(&bob_public).to_bytes(), shared);
+    }
+
+    #[test]
+    fn corresponds_to_ed25519_and_x25519() {
+        use curve25519_dalek::constants::ED25519_BASEPOINT_TABLE;
+
+        let csprng: OsRng = OsRng;
+        let secret = StaticSecret::new(csprng);
+        let public = PublicKey::from(&secret);
+
+        let ed25519 = (&secret.0 * &ED25519_BASEPOINT_TABLE).compress().to_bytes();
+        assert!(public.corresponds_to_ed25519(&ed25519));
+        assert_eq!(PublicKey::from_ed25519_public_key(&ed25519), Some(public));
+
+        let x25519 = x25519_dalek::PublicKey::from(&secret.to_x25519_static_secret());
+        assert!(public.corresponds_to_x25519(&x25519));
+
+        let other = PublicKey::from(&StaticSecret::new(csprng));
+        assert!(!other.corresponds_to_ed25519(&ed25519));
+        assert!(!other.corresponds_to_x25519(&x25519));
+    }
+
+    #[test]
+    fn static_secret_to_bytes_zeroizing() {
+        let secret = StaticSecret::new(OsRng);
+        let bytes = secret.to_bytes_zeroizing();
+
+        assert_eq!(*bytes, secret.to_bytes());
+        assert_eq!(StaticSecret::from(*bytes).to_bytes(), secret.to_bytes());
+    }
+
+    #[test]
+    fn const_public_key() {
+        const BASEPOINT_BYTES: [u8; 64] = WEI25519_BASEPOINT.to_bytes();
+        const BASEPOINT: PublicKey = PublicKey::from_bytes(&BASEPOINT_BYTES);
+
+        assert_eq!(BASEPOINT, PublicKey(WEI25519_BASEPOINT));
+        assert_eq!(PublicKey::parse_auto(&BASEPOINT.to_bytes()), Some(BASEPOINT));
+    }
+
+    #[test]
+    fn x_only_public_key() {
+        let public = PublicKey::from(&StaticSecret::new(OsRng));
+        let (x_only, y_is_odd) = public.x_only_public_key();
+        assert_eq!(y_is_odd, public.y_is_odd());
+        assert_eq!(x_only.to_bytes()[..], public.compress()[1..]);
+
+        let x_only = XOnlyPublicKey::from_bytes(&x_only.to_bytes()).unwrap();
+        assert_eq!(x_only.public_key(y_is_odd), Some(public));
+        assert_eq!(XOnlyPublicKey::from(public), x_only);
+
+        // The even-y convention
+        let even = x_only.to_public_key().unwrap();
+        assert!(!even.y_is_odd());
+        assert_eq!(even.x_only_public_key(), (x_only, false));
+        let odd = x_only.public_key(true).unwrap();
+        assert!(odd.y_is_odd());
+        assert_eq!(odd.0 + even.0, WeierstrassPoint::default());
+
+        // Non-canonical x-coordinates are rejected.
+        assert_eq!(XOnlyPublicKey::from_bytes(&[0xff; 32]), None);
+    }
+
+    #[test]
+    fn x_only_public_key_without_point() {
+        // An x-coordinate without a point on Wei25519, as dropped from an unchecked PublicKey
+        let mut x = [0u8; 32];
+        while XOnlyPublicKey::from_bytes(&x).is_some() {
+            x[31] += 1;
+        }
+
+        assert_eq!(XOnlyPublicKey(x).to_public_key(), None);
+        assert_eq!(XOnlyPublicKey(x).public_key(true), None);
+    }
+
+    #[test]
+    fn parse_auto_detects_encodings() {
+        let secret = StaticSecret::new(OsRng);
+        let public = PublicKey::from(&secret);
+
+        let mut uncompressed = [0x04u8; 65];
+        uncompressed[1..33].copy_from_slice(&public.0.x);
+        uncompressed[1..33].reverse();
+        uncompressed[33..].copy_from_slice(&public.0.y);
+        uncompressed[33..].reverse();
+
+        assert_eq!(PublicKey::parse_auto(&public.compress()), Some(public));
+        assert_eq!(PublicKey::parse_auto(&public.to_bytes()), Some(public));
+        assert_eq!(PublicKey::parse_auto(&uncompressed), Some(public));
+
+        let x25519_public = public.to_x25519_public_key();
+        let parsed = PublicKey::parse_auto(x25519_public.as_bytes()).unwrap();
+        assert!(!parsed.0.y_is_odd());
+        assert!(parsed.corresponds_to_x25519(&x25519_public));
+
+        let peer = StaticSecret::new(OsRng);
+        assert_eq!(
+            peer.diffie_hellman(&parsed).to_bytes(),
+            peer.diffie_hellman(&public).to_bytes()
+        );
+
+        // Unknown lengths and tags, the point at infinity and non-canonical u are rejected.
+        uncompressed[0] = 0x06;
+        assert_eq!(PublicKey::parse_auto(&uncompressed), None);
+        assert_eq!(PublicKey::parse_auto(&public.to_bytes()[..63]), None);
+        assert_eq!(PublicKey::parse_auto(&[0u8; 32]), None);
+        assert_eq!(PublicKey::parse_auto(&[0u8; 64]), None);
+
+        let mut high_bit = *x25519_public.as_bytes();
+        high_bit[31] |= 0x80;
+        assert_eq!(PublicKey::parse_auto(&high_bit), None);
+    }
+
+    #[test]
+    fn tweaked_keys_match() {
+        let secret = StaticSecret::new(OsRng);
+        let public = PublicKey::from(&secret);
+        let tweak = Scalar::random(&mut OsRng);
+
+        let added = secret.add_tweak(&tweak).unwrap();
+        assert_eq!(Some(PublicKey::from(&added)), public.add_tweak(&tweak));
+
+        let multiplied = secret.mul_tweak(&tweak).unwrap();
+        assert_eq!(Some(PublicKey::from(&multiplied)), public.mul_tweak(&tweak));
+
+        let peer = StaticSecret::new(OsRng);
+        assert_eq!(
+            added.diffie_hellman(&PublicKey::from(&peer)).to_bytes(),
+            peer.diffie_hellman(&public.add_tweak(&tweak).unwrap()).to_bytes()
+        );
+
+        // The child keys round-trip through their bytes.
+        let restored = DerivedSecret::from_bytes(added.to_bytes()).unwrap();
+        assert_eq!(PublicKey::from(&restored), PublicKey::from(&added));
+        let restored = DerivedSecret::from_bytes(multiplied.to_bytes()).unwrap();
+        assert_eq!(PublicKey::from(&restored), PublicKey::from(&multiplied));
+
+        // Tweaks which cancel the key are rejected.
+        assert!(secret.mul_tweak(&Scalar::zero()).is_none());
+        assert!(secret.add_tweak(&-secret.0.reduce()).is_none());
+        assert!(public.add_tweak(&-secret.0.reduce()).is_none());
+    }
+
+    #[test]
+    fn tweak_chains_match() {
+        let secret = StaticSecret::new(OsRng);
+        let public = PublicKey::from(&secret);
+        let first = Scalar::random(&mut OsRng);
+        let second = Scalar::random(&mut OsRng);
+
+        let added = secret.add_tweak(&first).unwrap().add_tweak(&second).unwrap();
+        let added_public = public.add_tweak(&first).unwrap().add_tweak(&second).unwrap();
+        assert_eq!(PublicKey::from(&added), added_public);
+
+        let mixed = secret.add_tweak(&first).unwrap().mul_tweak(&second).unwrap();
+        let mixed_public = public.add_tweak(&first).unwrap().mul_tweak(&second).unwrap();
+        assert_eq!(PublicKey::from(&mixed), mixed_public);
+
+        let peer = StaticSecret::new(OsRng);
+        assert_eq!(
+            mixed.diffie_hellman(&PublicKey::from(&peer)).to_bytes(),
+            peer.diffie_hellman(&mixed_public).to_bytes()
+        );
+
+        let child = secret.add_tweak(&first).unwrap();
+        assert!(child.mul_tweak(&Scalar::zero()).is_none());
+        assert!(child.add_tweak(&-child.0).is_none());
+    }
+
+    #[test]
+    fn diffie_hellman_checked() {
+        let csprng: OsRng = OsRng;
+
+        let a_secret = StaticSecret::new(csprng);
+        let b_secret = StaticSecret::new(csprng);
+        let b_pubkey = PublicKey::from(&b_secret);
+
+        let shared = a_secret.diffie_hellman(&b_pubkey);
+        let shared_checked = a_secret.diffie_hellman_checked(&b_pubkey).unwrap();
+        assert_eq!(shared.to_bytes(), shared_checked.to_bytes());
+
+        let mut off_curve = b_pubkey.to_bytes();
+        off_curve[32] ^= 1;
+        assert!(a_secret.diffie_hellman_checked(&PublicKey::from(off_curve)).is_none());
+    }
+
+    #[test]
+    fn diffie_hellman_checked_rejects_twist_torsion() {
+        // (1, 0) is a point of order 2 on a twist of Wei25519, which a clamped scalar sends to
+        // the point at infinity
+        let mut one = [0u8; 32];
+        one[0] = 1;
+        let twist_torsion = PublicKey(WeierstrassPoint { x: one, y: [0u8; 32] });
+        assert!(!twist_torsion.0.is_on_curve());
+
+        let secret = StaticSecret::new(OsRng);
+        assert_eq!(secret.0 * twist_torsion.0, WeierstrassPoint::default());
+        assert!(secret.diffie_hellman_checked(&twist_torsion).is_none());
+        assert!(EphemeralSecret::new(OsRng).diffie_hellman_checked(&twist_torsion).is_none());
+
+        let derived = secret.add_tweak(&Scalar::one()).unwrap();
+        assert!(derived.diffie_hellman_checked(&twist_torsion).is_none());
+
+        let mut u = [0u8; 32];
+        let mut v = [0u8; 32];
+        let montgomery = twist_torsion.0.into_montgomery();
+        u.copy_from_slice(&montgomery.0);
+        v.copy_from_slice(&montgomery.1);
+        assert!(w25519_checked(secret.to_bytes(), u, v).is_none());
+    }
+
+    #[test]
+    fn w25519_checked_matches_w25519() {
+        let mut csprng: OsRng = OsRng;
+
+        let k: Scalar = Scalar::random(&mut csprng);
+        let expected = w25519(k.to_bytes(), W25519_BASEPOINT_BYTES_U, W25519_BASEPOINT_BYTES_V);
+        let result = w25519_checked(k.to_bytes(), W25519_BASEPOINT_BYTES_U, W25519_BASEPOINT_BYTES_V);
+
+        assert_eq!(result, Some(expected));
+    }
+
     #[test]
     fn w25519_x25519_dh_key_exchange() {
         let csprng: OsRng = OsRng;
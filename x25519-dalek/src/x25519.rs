@@ -31,11 +31,11 @@ use zeroize::Zeroize;
 /// should they wish to erase public keys from memory.  Note that this erasure
 /// (in this crate) does *not* automatically happen, but either must be derived
 /// for Drop or explicitly called.
-#[cfg_attr(feature = "serde", serde(crate = "our_serde"))]
 #[cfg_attr(
     feature = "serde",
     derive(our_serde::Serialize, our_serde::Deserialize)
 )]
+#[cfg_attr(feature = "serde", serde(crate = "our_serde"))]
 #[derive(PartialEq, Eq, Hash, Copy, Clone, Debug, Zeroize)]
 pub struct PublicKey(pub(crate) MontgomeryPoint);
 
@@ -159,11 +159,11 @@ impl<'a> From<&'a ReusableSecret> for PublicKey {
 /// [`EphemeralSecret`] at all times, as that type enforces at compile-time that
 /// secret keys are never reused, which can have very serious security
 /// implications for many protocols.
-#[cfg_attr(feature = "serde", serde(crate = "our_serde"))]
 #[cfg_attr(
     feature = "serde",
     derive(our_serde::Serialize, our_serde::Deserialize)
 )]
+#[cfg_attr(feature = "serde", serde(crate = "our_serde"))]
 #[derive(Clone, Zeroize)]
 #[zeroize(drop)]
 pub struct StaticSecret(
@@ -210,6 +210,15 @@ impl<'a> From<&'a StaticSecret> for PublicKey {
 ///
 /// Each party computes this using their [`EphemeralSecret`] or [`StaticSecret`] and their
 /// counterparty's [`PublicKey`].
+///
+/// With the `serialize_shared_secrets` feature this type implements serde's `Serialize` and
+/// `Deserialize`.  Serialized shared secrets are no longer zeroized by this crate, so only
+/// enable it if derived secrets have to be persisted or cross a process boundary.
+#[cfg_attr(
+    feature = "serialize_shared_secrets",
+    derive(our_serde::Serialize, our_serde::Deserialize)
+)]
+#[cfg_attr(feature = "serialize_shared_secrets", serde(crate = "our_serde"))]
 #[derive(Zeroize)]
 #[zeroize(drop)]
 pub struct SharedSecret(pub(crate) MontgomeryPoint);
@@ -333,11 +342,11 @@ pub const X25519_BASEPOINT_BYTES: [u8; 32] = [
 /// Derived serialization methods will not work on a StaticSecret because x25519 requires
 /// non-canonical scalars which are rejected by curve25519-dalek. Thus we provide a way to convert
 /// the bytes directly to a scalar using Serde's remote derive functionality.
-#[cfg_attr(feature = "serde", serde(crate = "our_serde"))]
 #[cfg_attr(
     feature = "serde",
     derive(our_serde::Serialize, our_serde::Deserialize)
 )]
+#[cfg_attr(feature = "serde", serde(crate = "our_serde"))]
 #[cfg_attr(feature = "serde", serde(remote = "Scalar"))]
 struct AllowUnreducedScalarBytes(
     #[cfg_attr(feature = "serde", serde(getter = "Scalar::to_bytes"))] [u8; 32],
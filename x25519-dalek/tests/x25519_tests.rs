@@ -79,6 +79,21 @@ fn serde_bincode_static_secret_matches_from_bytes() {
     assert_eq!(decoded.to_bytes(), expected.to_bytes());
 }
 
+#[test]
+#[cfg(feature = "serialize_shared_secrets")]
+fn serde_bincode_shared_secret_roundtrip() {
+    use bincode;
+
+    let static_secret = StaticSecret::from([0x24; 32]);
+    let shared_secret = static_secret.diffie_hellman(&PublicKey::from(X25519_BASEPOINT_BYTES));
+
+    let encoded = bincode::serialize(&shared_secret).unwrap();
+    let decoded: SharedSecret = bincode::deserialize(&encoded).unwrap();
+
+    assert_eq!(encoded.len(), 32);
+    assert_eq!(decoded.as_bytes(), shared_secret.as_bytes());
+}
+
 fn do_rfc7748_ladder_test1(input_scalar: [u8; 32], input_point: [u8; 32], expected: [u8; 32]) {
     let result = x25519(input_scalar, input_point);
 